@@ -0,0 +1,123 @@
+//! Times the BSP heavy operations on a large synthetic model, to compare the serial and parallel paths.
+//!
+//! Run it once with and once without the `parallel` feature, e.g.
+//!
+//! ```text
+//! cargo run --release --example parallel_bsp
+//! cargo run --release --example parallel_bsp --features parallel
+//! ```
+//!
+//! The checksums printed at the end must match between the two runs, the parallel path has to produce identical files.
+
+use pof::{parse_bytes, BspData, BspStrategy, Model, NormalId, ObjectId, PolyVertex, Polygon, SubObject, TextureId, Vec3d, VertexId};
+use std::f32::consts::{PI, TAU};
+use std::time::Instant;
+
+const NUM_SUBOBJECTS: u32 = 200;
+const RINGS: u32 = 60;
+const SEGMENTS: u32 = 32;
+
+// a lumpy sphere, so the trees aren't trivially regular
+fn lumpy_sphere(seed: u32, radius: f32) -> (Vec<Vec3d>, Vec<Vec3d>, Vec<Polygon>) {
+    let (mut verts, mut norms) = (vec![], vec![]);
+    for ring in 0..RINGS {
+        let phi = (ring as f32 + 0.5) / RINGS as f32 * PI;
+        for seg in 0..SEGMENTS {
+            let theta = seg as f32 / SEGMENTS as f32 * TAU;
+            let dir = Vec3d::new(phi.sin() * theta.cos(), phi.sin() * theta.sin(), phi.cos());
+            let lump = 1.0 + 0.2 * ((seed + ring * 7 + seg * 13) as f32).sin();
+            verts.push(dir * (radius * lump));
+            norms.push(dir);
+        }
+    }
+
+    let mut polygons = vec![];
+    for ring in 0..RINGS - 1 {
+        for seg in 0..SEGMENTS {
+            let a = ring * SEGMENTS + seg;
+            let b = ring * SEGMENTS + (seg + 1) % SEGMENTS;
+            let (c, d) = (a + SEGMENTS, b + SEGMENTS);
+            for tri in [[a, b, d], [a, d, c]] {
+                polygons.push(Polygon {
+                    normal: Vec3d::ZERO,
+                    texture: TextureId(0),
+                    verts: tri
+                        .iter()
+                        .map(|&i| PolyVertex {
+                            vertex_id: VertexId(i),
+                            normal_id: NormalId(i),
+                            uv: (0.0, 0.0),
+                        })
+                        .collect(),
+                });
+            }
+        }
+    }
+    (verts, norms, polygons)
+}
+
+fn synthetic_model() -> Model {
+    let mut model = Model::default();
+    model.textures.push("hull".to_string());
+    for i in 0..NUM_SUBOBJECTS {
+        let (verts, norms, polygons) = lumpy_sphere(i, 10.0 + (i % 7) as f32);
+        let mut subobj = SubObject::default();
+        subobj.obj_id = ObjectId(i);
+        subobj.name = if i == 0 { "detail0".to_string() } else { format!("turret{:03}", i) };
+        subobj.parent = if i == 0 { None } else { Some(ObjectId(0)) };
+        subobj.offset = Vec3d::new((i % 20) as f32 * 30.0, (i / 20) as f32 * 30.0, 0.0);
+        // a throwaway tree, just to hold the polygons until the timed rebuild
+        subobj.bsp_data = BspData {
+            collision_tree: BspData::recalculate(&verts, polygons.into_iter()),
+            verts,
+            norms,
+        };
+        model.sub_objects.push(subobj);
+    }
+    model.header.num_subobjects = NUM_SUBOBJECTS;
+    model.header.detail_levels.push(ObjectId(0));
+    model.recalc_all_children_ids();
+    model
+}
+
+fn time<T>(label: &str, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let result = f();
+    println!("{:<24}{:>10.1} ms", label, start.elapsed().as_secs_f64() * 1000.0);
+    result
+}
+
+// FNV-1a, just to compare outputs between runs
+fn checksum(bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(0xcbf29ce484222325, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+fn main() {
+    println!(
+        "{} subobjects of {} polygons each, parallel feature {}",
+        NUM_SUBOBJECTS,
+        (RINGS - 1) * SEGMENTS * 2,
+        if cfg!(feature = "parallel") { "on" } else { "off" }
+    );
+
+    let mut model = synthetic_model();
+    time("recalculate (median)", || model.recalculate_geometry(BspStrategy::Median));
+    time("recalculate (SAH)", || model.recalculate_geometry(BspStrategy::SurfaceArea));
+
+    let mut written = vec![];
+    time("write", || model.write(&mut written).unwrap());
+    let parsed = time("parse", || parse_bytes(&written).unwrap());
+
+    let matrix = nalgebra_glm::rotation(0.3, &nalgebra_glm::vec3(1.0, 2.0, 3.0));
+    time("transform", || model.apply_transform(&matrix));
+    let mut transformed = vec![];
+    model.write(&mut transformed).unwrap();
+
+    let mut reparsed = vec![];
+    parsed.write(&mut reparsed).unwrap();
+    assert!(reparsed == written, "parsing changed the model");
+
+    println!("checksums: written {:016x}, transformed {:016x}", checksum(&written), checksum(&transformed));
+}
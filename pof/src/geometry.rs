@@ -0,0 +1,756 @@
+// general purpose mesh algorithms backing the tools that generate geometry (shields, LODs and such)
+// everything here works on plain triangle lists indexing into a vertex list, triangles wound so that
+// (v2 - v1) x (v3 - v1) points out of the mesh, same as shield polygons
+
+use crate::{BoundingBox, Vec3d};
+use glm::{DVec2, DVec3};
+use nalgebra_glm as glm;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+// f32 is fine for storing a model, but the predicates here need all the precision they can get
+fn dvec(vert: Vec3d) -> DVec3 {
+    glm::vec3(vert.x as f64, vert.y as f64, vert.z as f64)
+}
+
+fn tri_normal(verts: &[DVec3], tri: [u32; 3]) -> DVec3 {
+    let [a, b, c] = tri.map(|i| verts[i as usize]);
+    (b - a).cross(&(c - a))
+}
+
+/// Drops the vertices the triangles don't use, returning the remaining vertices and the triangles renumbered to match.
+pub(crate) fn compact(verts: &[Vec3d], tris: &[[u32; 3]]) -> (Vec<Vec3d>, Vec<[u32; 3]>) {
+    let mut map = HashMap::new();
+    let mut new_verts = vec![];
+    let new_tris = tris
+        .iter()
+        .map(|tri| {
+            tri.map(|i| {
+                *map.entry(i).or_insert_with(|| {
+                    new_verts.push(verts[i as usize]);
+                    new_verts.len() as u32 - 1
+                })
+            })
+        })
+        .collect();
+    (new_verts, new_tris)
+}
+
+/// Merges together vertices no further apart than `distance`, each onto the first of them, returning the vertices that remain
+/// and where each of the original vertices ended up in them.
+pub(crate) fn weld(verts: &[Vec3d], distance: f32) -> (Vec<Vec3d>, Vec<u32>) {
+    let mut welded: Vec<Vec3d> = vec![];
+    let mut remap = vec![];
+    let is_finite = |vert: &Vec3d| vert.x.is_finite() && vert.y.is_finite() && vert.z.is_finite();
+
+    if distance.is_nan() || distance <= 0.0 {
+        // only exact duplicates, which can just be looked up by their bits (adding 0.0 so that -0.0 and 0.0 are the same)
+        let mut seen: HashMap<[u32; 3], u32> = HashMap::new();
+        for &vert in verts {
+            let bits = [vert.x, vert.y, vert.z].map(|x| (x + 0.0).to_bits());
+            let existing = if is_finite(&vert) { seen.get(&bits).copied() } else { None };
+            remap.push(existing.unwrap_or_else(|| {
+                welded.push(vert);
+                let i = welded.len() as u32 - 1;
+                seen.insert(bits, i);
+                i
+            }));
+        }
+        return (welded, remap);
+    }
+
+    // bucketing the vertices into a grid so only the neighboring cells need to be searched
+    // the cells can't be too small for the model, or the cell coordinates run out of range
+    let mut bbox = BoundingBox::EMPTY;
+    for &vert in verts.iter().filter(|vert| is_finite(vert)) {
+        bbox.expand_vec(vert);
+    }
+    let size = if bbox.is_inverted() {
+        0.0
+    } else {
+        bbox.x_width().max(bbox.y_height()).max(bbox.z_length())
+    };
+    let cell_size = distance.max(size * 1e-6).max(f32::MIN_POSITIVE);
+    let cell = |vert: Vec3d| [vert.x, vert.y, vert.z].map(|x| (x / cell_size).floor() as i64);
+    let mut grid: HashMap<[i64; 3], Vec<u32>> = HashMap::new();
+    for &vert in verts {
+        let [x, y, z] = cell(vert);
+        let existing = (-1..=1)
+            .flat_map(|dx: i64| {
+                (-1..=1).flat_map(move |dy: i64| (-1..=1).map(move |dz: i64| [x.saturating_add(dx), y.saturating_add(dy), z.saturating_add(dz)]))
+            })
+            .filter_map(|key| grid.get(&key))
+            .flatten()
+            .find(|&&i| (welded[i as usize] - vert).magnitude() <= distance)
+            .copied();
+        remap.push(existing.unwrap_or_else(|| {
+            welded.push(vert);
+            let i = welded.len() as u32 - 1;
+            grid.entry([x, y, z]).or_default().push(i);
+            i
+        }));
+    }
+    (welded, remap)
+}
+
+/// Computes the convex hull of the points using quickhull, returning its triangles as indices into `points`.
+///
+/// Returns `None` if the points don't enclose any volume (fewer than 4, or all on a plane), or if any of them aren't finite.
+pub(crate) fn convex_hull(points: &[Vec3d]) -> Option<Vec<[u32; 3]>> {
+    struct Face {
+        verts: [u32; 3],
+        normal: DVec3,
+        dist: f64,
+        // the points in front of this face, which it is responsible for
+        outside: Vec<u32>,
+        alive: bool,
+    }
+
+    let pts = &points.iter().map(|&vert| dvec(vert)).collect::<Vec<_>>();
+    // a broken import can leave NaNs about, which have no place on any hull
+    if pts.len() < 4 || pts.iter().any(|p| !p.iter().all(|x| x.is_finite())) {
+        return None;
+    }
+
+    // the usual quickhull tolerance, scaled to the size of the input
+    let scale = (0..3).map(|axis| pts.iter().map(|p| p[axis].abs()).fold(0.0, f64::max)).sum::<f64>();
+    let eps = 3.0 * f64::EPSILON * scale.max(1.0);
+
+    let make_face = |verts: [u32; 3]| {
+        let normal = tri_normal(pts, verts).normalize();
+        Face {
+            verts,
+            normal,
+            dist: normal.dot(&pts[verts[0] as usize]),
+            outside: vec![],
+            alive: true,
+        }
+    };
+    let height = |face: &Face, i: u32| face.normal.dot(&pts[i as usize]) - face.dist;
+
+    // start with a tetrahedron as big as can easily be found
+    let extremes = (0..3)
+        .flat_map(|axis| {
+            let cmp = move |a: &usize, b: &usize| pts[*a][axis].total_cmp(&pts[*b][axis]);
+            [(0..pts.len()).min_by(cmp).unwrap(), (0..pts.len()).max_by(cmp).unwrap()]
+        })
+        .collect::<Vec<_>>();
+    let (i0, i1) = extremes
+        .iter()
+        .flat_map(|&a| extremes.iter().map(move |&b| (a, b)))
+        .max_by(|(a1, b1), (a2, b2)| (pts[*a1] - pts[*b1]).norm().total_cmp(&(pts[*a2] - pts[*b2]).norm()))
+        .unwrap();
+    let line = (pts[i1] - pts[i0]).normalize();
+    let line_dist = |i: usize| {
+        let p = pts[i] - pts[i0];
+        (p - line * p.dot(&line)).norm()
+    };
+    let i2 = (0..pts.len()).max_by(|&a, &b| line_dist(a).total_cmp(&line_dist(b))).unwrap();
+    if line_dist(i2) <= eps {
+        return None;
+    }
+    let base = make_face([i0 as u32, i1 as u32, i2 as u32]);
+    let i3 = (0..pts.len())
+        .max_by(|&a, &b| height(&base, a as u32).abs().total_cmp(&height(&base, b as u32).abs()))
+        .unwrap();
+    if height(&base, i3 as u32).abs() <= eps {
+        return None;
+    }
+
+    let mut faces = vec![];
+    let tetra = [i0, i1, i2, i3].map(|i| i as u32);
+    for (skip, &opposite) in tetra.iter().enumerate() {
+        let mut verts = [0; 3];
+        let mut n = 0;
+        for (i, &vert) in tetra.iter().enumerate() {
+            if i != skip {
+                verts[n] = vert;
+                n += 1;
+            }
+        }
+        let mut face = make_face(verts);
+        if height(&face, opposite) > 0.0 {
+            face = make_face([verts[0], verts[2], verts[1]]);
+        }
+        faces.push(face);
+    }
+
+    // directed edge -> the face it belongs to, the face across an edge (a, b) is the one owning (b, a)
+    let mut edges = HashMap::new();
+    for (i, face) in faces.iter().enumerate() {
+        let [a, b, c] = face.verts;
+        for edge in [(a, b), (b, c), (c, a)] {
+            edges.insert(edge, i);
+        }
+    }
+
+    let assign = |faces: &mut Vec<Face>, candidates: &[usize], point: u32| {
+        if let Some(&i) = candidates.iter().find(|&&i| height(&faces[i], point) > eps) {
+            faces[i].outside.push(point);
+        }
+    };
+    for i in 0..pts.len() as u32 {
+        if !tetra.contains(&i) {
+            assign(&mut faces, &[0, 1, 2, 3], i);
+        }
+    }
+
+    let mut stack = (0..4).collect::<Vec<_>>();
+    while let Some(face_idx) = stack.pop() {
+        if !faces[face_idx].alive || faces[face_idx].outside.is_empty() {
+            continue;
+        }
+        let face = &faces[face_idx];
+        let eye = *face
+            .outside
+            .iter()
+            .max_by(|&&a, &&b| height(face, a).total_cmp(&height(face, b)))
+            .unwrap();
+
+        // find every face the eye point can see, and the horizon around them
+        let mut visible = vec![face_idx];
+        let mut visited = HashSet::from([face_idx]);
+        let mut horizon = vec![];
+        let mut i = 0;
+        while i < visible.len() {
+            let [a, b, c] = faces[visible[i]].verts;
+            for (a, b) in [(a, b), (b, c), (c, a)] {
+                let &neighbor = edges.get(&(b, a))?;
+                if visited.contains(&neighbor) {
+                    continue;
+                }
+                if height(&faces[neighbor], eye) > eps {
+                    visited.insert(neighbor);
+                    visible.push(neighbor);
+                } else {
+                    horizon.push((a, b));
+                }
+            }
+            i += 1;
+        }
+
+        let mut orphans = vec![];
+        for &i in &visible {
+            let face = &mut faces[i];
+            face.alive = false;
+            orphans.append(&mut face.outside);
+            let [a, b, c] = face.verts;
+            for edge in [(a, b), (b, c), (c, a)] {
+                edges.remove(&edge);
+            }
+        }
+
+        let mut new_faces = vec![];
+        for (a, b) in horizon {
+            let face = make_face([a, b, eye]);
+            faces.push(face);
+            let i = faces.len() - 1;
+            for edge in [(a, b), (b, eye), (eye, a)] {
+                edges.insert(edge, i);
+            }
+            new_faces.push(i);
+        }
+        for point in orphans {
+            if point != eye {
+                assign(&mut faces, &new_faces, point);
+            }
+        }
+        stack.extend(new_faces);
+    }
+
+    Some(faces.into_iter().filter(|face| face.alive).map(|face| face.verts).collect())
+}
+
+// a quadric, the sum of squared distances to a set of planes, as the upper triangle of a symmetric 4x4 matrix
+#[derive(Clone, Copy, Default)]
+struct Quadric([f64; 10]);
+impl Quadric {
+    fn plane(normal: DVec3, point: DVec3, weight: f64) -> Self {
+        let [a, b, c] = [normal.x, normal.y, normal.z];
+        let d = -normal.dot(&point);
+        Quadric([a * a, a * b, a * c, a * d, b * b, b * c, b * d, c * c, c * d, d * d].map(|x| x * weight))
+    }
+
+    fn add(&mut self, other: &Quadric) {
+        for (x, y) in self.0.iter_mut().zip(other.0) {
+            *x += y;
+        }
+    }
+
+    fn error(&self, p: DVec3) -> f64 {
+        let [aa, ab, ac, ad, bb, bc, bd, cc, cd, dd] = self.0;
+        let (x, y, z) = (p.x, p.y, p.z);
+        aa * x * x
+            + 2.0 * ab * x * y
+            + 2.0 * ac * x * z
+            + 2.0 * ad * x
+            + bb * y * y
+            + 2.0 * bc * y * z
+            + 2.0 * bd * y
+            + cc * z * z
+            + 2.0 * cd * z
+            + dd
+    }
+}
+
+// a pending collapse of `from` onto `to`, ordered cheapest first for the heap
+struct Collapse {
+    cost: f64,
+    from: u32,
+    to: u32,
+    // the versions of the two vertices when this was computed, if either changed since its stale
+    versions: (u32, u32),
+}
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+impl Eq for Collapse {}
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for Collapse {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost).then((other.from, other.to).cmp(&(self.from, self.to)))
+    }
+}
+
+/// Simplifies a triangle mesh down to at most `target_tris` triangles, or as close as it can get without tearing or folding it,
+/// by collapsing the edges that change the surface the least (by quadric error).
+///
+/// Edges are only ever collapsed onto one of their ends, so the vertices that remain keep their exact positions,
+/// and the triangles returned still index into `verts`, each alongside the index of the triangle in `tris` it started out as.
+/// Open boundaries are kept in place, so meshes that aren't closed keep their silhouette,
+/// and vertices marked in `pinned` never move at all (any past the end of it are free to).
+pub(crate) fn decimate(verts: &[Vec3d], tris: &[[u32; 3]], target_tris: usize, pinned: &[bool]) -> Vec<(usize, [u32; 3])> {
+    let pts = verts.iter().map(|&vert| dvec(vert)).collect::<Vec<_>>();
+    let (origins, mut tris): (Vec<_>, Vec<_>) = tris
+        .iter()
+        .copied()
+        .enumerate()
+        .filter(|(_, tri)| tri[0] != tri[1] && tri[1] != tri[2] && tri[2] != tri[0])
+        .unzip();
+    let mut alive = vec![true; tris.len()];
+    let mut num_alive = tris.len();
+
+    // the triangles around each vertex, which may include dead ones
+    let mut vert_tris = vec![vec![]; pts.len()];
+    let mut quadrics = vec![Quadric::default(); pts.len()];
+    let mut edge_count = HashMap::new();
+    for (i, &tri) in tris.iter().enumerate() {
+        let normal = tri_normal(&pts, tri);
+        let area = normal.norm();
+        if area > 0.0 {
+            let plane = Quadric::plane(normal / area, pts[tri[0] as usize], area);
+            for vert in tri {
+                quadrics[vert as usize].add(&plane);
+            }
+        }
+        for (j, &vert) in tri.iter().enumerate() {
+            vert_tris[vert as usize].push(i as u32);
+            let next = tri[(j + 1) % 3];
+            *edge_count.entry((vert.min(next), vert.max(next))).or_insert(0) += 1;
+        }
+    }
+
+    // pin down the open edges with steep planes running along them, so collapses can slide along a boundary but not off it
+    let mut on_boundary = vec![false; pts.len()];
+    for &tri in &tris {
+        for j in 0..3 {
+            let (a, b) = (tri[j], tri[(j + 1) % 3]);
+            if edge_count[&(a.min(b), a.max(b))] == 1 {
+                on_boundary[a as usize] = true;
+                on_boundary[b as usize] = true;
+                let edge = pts[b as usize] - pts[a as usize];
+                let side = edge.cross(&tri_normal(&pts, tri));
+                if side.norm() > 0.0 {
+                    let plane = Quadric::plane(side.normalize(), pts[a as usize], 1000.0 * edge.norm_squared());
+                    quadrics[a as usize].add(&plane);
+                    quadrics[b as usize].add(&plane);
+                }
+            }
+        }
+    }
+
+    let neighbors = |vert_tris: &[Vec<u32>], tris: &[[u32; 3]], alive: &[bool], vert: u32| {
+        let mut out = vec![];
+        for &t in &vert_tris[vert as usize] {
+            if alive[t as usize] {
+                for other in tris[t as usize] {
+                    if other != vert && !out.contains(&other) {
+                        out.push(other);
+                    }
+                }
+            }
+        }
+        out
+    };
+
+    let mut versions = vec![0u32; pts.len()];
+    let mut heap = BinaryHeap::new();
+    let push = |heap: &mut BinaryHeap<Collapse>, quadrics: &[Quadric], versions: &[u32], from: u32, to: u32| {
+        let mut quadric = quadrics[from as usize];
+        quadric.add(&quadrics[to as usize]);
+        let cost = quadric.error(pts[to as usize]);
+        heap.push(Collapse {
+            cost,
+            from,
+            to,
+            versions: (versions[from as usize], versions[to as usize]),
+        });
+    };
+    for vert in 0..pts.len() as u32 {
+        for other in neighbors(&vert_tris, &tris, &alive, vert) {
+            push(&mut heap, &quadrics, &versions, vert, other);
+        }
+    }
+
+    while num_alive > target_tris {
+        let Some(Collapse { from, to, versions: (from_version, to_version), .. }) = heap.pop() else {
+            break;
+        };
+        if versions[from as usize] != from_version || versions[to as usize] != to_version {
+            continue;
+        }
+        if pinned.get(from as usize).copied().unwrap_or(false) {
+            continue;
+        }
+        let from_neighbors = neighbors(&vert_tris, &tris, &alive, from);
+        if !from_neighbors.contains(&to) {
+            continue;
+        }
+
+        // a boundary vertex may only move along the boundary, or the hole it borders would grow
+        let shared = vert_tris[from as usize]
+            .iter()
+            .filter(|&&t| alive[t as usize] && tris[t as usize].contains(&to))
+            .count();
+        if on_boundary[from as usize] && shared != 1 {
+            continue;
+        }
+
+        // the link condition: the ends may only share the neighbors across the triangles being removed,
+        // anything more and the collapse would pinch the mesh into something non-manifold
+        let to_neighbors = neighbors(&vert_tris, &tris, &alive, to);
+        if from_neighbors.iter().filter(|vert| to_neighbors.contains(vert)).count() != shared {
+            continue;
+        }
+
+        // and no triangle may flip over or be squashed flat
+        let folds = vert_tris[from as usize].iter().any(|&t| {
+            let tri = tris[t as usize];
+            if !alive[t as usize] || tri.contains(&to) {
+                return false;
+            }
+            let before = tri_normal(&pts, tri);
+            let after = tri_normal(&pts, tri.map(|vert| if vert == from { to } else { vert }));
+            after.norm() <= 1e-12 * before.norm().max(f64::MIN_POSITIVE) || before.normalize().dot(&after.normalize()) < 0.2
+        });
+        if folds {
+            continue;
+        }
+
+        for t in std::mem::take(&mut vert_tris[from as usize]) {
+            if !alive[t as usize] {
+                continue;
+            }
+            let tri = &mut tris[t as usize];
+            if tri.contains(&to) {
+                alive[t as usize] = false;
+                num_alive -= 1;
+            } else {
+                *tri = tri.map(|vert| if vert == from { to } else { vert });
+                vert_tris[to as usize].push(t);
+            }
+        }
+        let quadric = quadrics[from as usize];
+        quadrics[to as usize].add(&quadric);
+        on_boundary[to as usize] |= on_boundary[from as usize];
+
+        versions[from as usize] += 1;
+        versions[to as usize] += 1;
+        for other in neighbors(&vert_tris, &tris, &alive, to) {
+            push(&mut heap, &quadrics, &versions, to, other);
+            push(&mut heap, &quadrics, &versions, other, to);
+        }
+    }
+
+    origins
+        .into_iter()
+        .zip(tris)
+        .zip(alive)
+        .filter(|(_, alive)| *alive)
+        .map(|(tri, _)| tri)
+        .collect()
+}
+
+// twice the signed area of the triangle, positive if it's counterclockwise
+fn orient_2d(a: DVec2, b: DVec2, c: DVec2) -> f64 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+fn signed_area_2d(points: &[DVec2], ring: &[u32]) -> f64 {
+    (0..ring.len())
+        .map(|i| {
+            let (a, b) = (points[ring[i] as usize], points[ring[(i + 1) % ring.len()] as usize]);
+            a.x * b.y - b.x * a.y
+        })
+        .sum::<f64>()
+        / 2.0
+}
+
+fn point_in_ring_2d(points: &[DVec2], ring: &[u32], point: DVec2) -> bool {
+    let mut inside = false;
+    for i in 0..ring.len() {
+        let (a, b) = (points[ring[i] as usize], points[ring[(i + 1) % ring.len()] as usize]);
+        if (a.y > point.y) != (b.y > point.y) && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+// whether the segments cross at a point inside both of them, touching ends don't count
+fn segments_cross_2d(a: DVec2, b: DVec2, c: DVec2, d: DVec2) -> bool {
+    let opposite = |x: f64, y: f64| (x > 0.0 && y < 0.0) || (x < 0.0 && y > 0.0);
+    opposite(orient_2d(a, b, c), orient_2d(a, b, d)) && opposite(orient_2d(c, d, a), orient_2d(c, d, b))
+}
+
+// links the hole into the ring around it, by a pair of edges to and from the closest ring vertex it can see
+// so the ring keeps going around the outside of the hole
+fn bridge_hole(points: &[DVec2], ring: &mut Vec<u32>, hole: &[u32], other_holes: &[Vec<u32>]) {
+    let start = (0..hole.len())
+        .max_by(|&a, &b| points[hole[a] as usize].x.total_cmp(&points[hole[b] as usize].x))
+        .unwrap();
+    let from = points[hole[start] as usize];
+
+    let edges = (std::iter::once(&ring[..]).chain(other_holes.iter().map(|hole| &hole[..])).chain([hole]))
+        .flat_map(|ring| (0..ring.len()).map(move |i| (ring[i], ring[(i + 1) % ring.len()])))
+        .collect::<Vec<_>>();
+    // the bridge has to leave the ring vertex on the inside of its corner, and not cross anything on the way
+    let visible = |i: usize| {
+        let n = ring.len();
+        let (prev, to, next) = (points[ring[(i + n - 1) % n] as usize], points[ring[i] as usize], points[ring[(i + 1) % n] as usize]);
+        let locally_inside = if orient_2d(prev, to, next) > 0.0 {
+            orient_2d(to, from, next) <= 0.0 && orient_2d(to, prev, from) <= 0.0
+        } else {
+            orient_2d(to, from, prev) > 0.0 || orient_2d(to, next, from) > 0.0
+        };
+        locally_inside
+            && !edges
+                .iter()
+                .any(|&(a, b)| segments_cross_2d(from, to, points[a as usize], points[b as usize]))
+    };
+    let mut candidates = (0..ring.len()).collect::<Vec<_>>();
+    candidates.sort_by(|&a, &b| {
+        let (a, b) = (points[ring[a] as usize], points[ring[b] as usize]);
+        (a.x < from.x)
+            .cmp(&(b.x < from.x))
+            .then((a - from).norm_squared().total_cmp(&(b - from).norm_squared()))
+    });
+    let Some(to) = candidates.into_iter().find(|&i| visible(i)) else {
+        return;
+    };
+
+    let around_hole = (0..=hole.len()).map(|i| hole[(start + i) % hole.len()]);
+    let bridged = around_hole.chain([ring[to]]).collect::<Vec<_>>();
+    ring.splice(to + 1..to + 1, bridged);
+}
+
+/// Triangulates the planar region bounded by `loops` by ear clipping, once each hole is bridged into the boundary around it.
+/// Boundaries have to wind counterclockwise around `normal` and holes clockwise; loops with no area are skipped,
+/// and holes outside of every boundary are dropped.
+///
+/// Returns triangles wound the same way, as indices into the points of all the loops one after the other,
+/// or `None` if the loops are malformed (crossing themselves or each other, or wound the wrong way) and can't be filled without a hole.
+pub(crate) fn triangulate(loops: &[Vec<Vec3d>], normal: Vec3d) -> Option<Vec<[u32; 3]>> {
+    let normal = dvec(normal).normalize();
+    let axis = if normal.x.abs() < 0.9 {
+        glm::vec3(1.0, 0.0, 0.0)
+    } else {
+        glm::vec3(0.0, 1.0, 0.0)
+    };
+    let u = axis.cross(&normal).normalize();
+    let v = normal.cross(&u);
+
+    let mut points = vec![];
+    let mut rings = vec![];
+    for points_3d in loops {
+        let start = points.len() as u32;
+        points.extend(points_3d.iter().map(|&point| DVec2::new(dvec(point).dot(&u), dvec(point).dot(&v))));
+        rings.push((start..points.len() as u32).collect::<Vec<_>>());
+    }
+
+    // each hole goes into the smallest boundary around it
+    let (mut boundaries, holes): (Vec<_>, Vec<_>) = rings
+        .into_iter()
+        .map(|ring| (signed_area_2d(&points, &ring), ring))
+        .filter(|(area, _)| *area != 0.0)
+        .partition(|(area, _)| *area > 0.0);
+    let mut boundary_holes = vec![vec![]; boundaries.len()];
+    for (_, hole) in holes {
+        let inside = (0..boundaries.len())
+            .filter(|&i| point_in_ring_2d(&points, &boundaries[i].1, points[hole[0] as usize]))
+            .min_by(|&a, &b| boundaries[a].0.total_cmp(&boundaries[b].0));
+        if let Some(i) = inside {
+            boundary_holes[i].push(hole);
+        }
+    }
+
+    let mut tris = vec![];
+    for ((_, ring), mut holes) in boundaries.iter_mut().zip(boundary_holes) {
+        // rightmost first, so the bridges of the later ones don't get in the way of the earlier ones
+        holes.sort_by(|a, b| {
+            let max_x = |hole: &Vec<u32>| hole.iter().map(|&i| points[i as usize].x).fold(f64::MIN, f64::max);
+            max_x(b).total_cmp(&max_x(a))
+        });
+        for i in 0..holes.len() {
+            bridge_hole(&points, ring, &holes[i], &holes[i + 1..]);
+        }
+
+        // a corner with no area to speak of, either a straight run or a spike doubling back on itself, can go without a triangle
+        let is_flat = |[pa, pb, pc]: [DVec2; 3]| orient_2d(pa, pb, pc).abs() <= 1e-6 * (pb - pa).norm() * (pc - pb).norm();
+
+        // clip off ears, convex corners with nothing else poking into them
+        // the points the bridges doubled up sit right on the corners, and don't count as poking in
+        let mut i = 0;
+        let mut since_last_ear = 0;
+        while ring.len() > 3 {
+            let n = ring.len();
+            let corner @ [a, b, c] = [ring[(i + n - 1) % n], ring[i], ring[(i + 1) % n]];
+            let corner_2d @ [pa, pb, pc] = corner.map(|i| points[i as usize]);
+            let is_ear = orient_2d(pa, pb, pc) > 0.0
+                && !ring.iter().any(|&p| {
+                    let p_2d = points[p as usize];
+                    !corner_2d.contains(&p_2d) && orient_2d(pa, pb, p_2d) >= 0.0 && orient_2d(pb, pc, p_2d) >= 0.0 && orient_2d(pc, pa, p_2d) >= 0.0
+                });
+            if is_ear {
+                tris.push([a, b, c]);
+                ring.remove(i);
+                i %= ring.len();
+                since_last_ear = 0;
+            } else if since_last_ear > n {
+                // out of ears, which only flat corners can cause short of the loops being malformed
+                let flat = (0..n).find(|&j| is_flat([ring[(j + n - 1) % n], ring[j], ring[(j + 1) % n]].map(|i| points[i as usize])))?;
+                ring.remove(flat);
+                i = flat % ring.len();
+                since_last_ear = 0;
+            } else {
+                i = (i + 1) % n;
+                since_last_ear += 1;
+            }
+        }
+        if let [a, b, c] = ring[..] {
+            let corner_2d = [a, b, c].map(|i| points[i as usize]);
+            if orient_2d(corner_2d[0], corner_2d[1], corner_2d[2]) > 0.0 {
+                tris.push([a, b, c]);
+            } else if !is_flat(corner_2d) {
+                return None;
+            }
+        }
+    }
+    Some(tris)
+}
+
+/// Where the ray from `origin` enters the box, as a distance in units of its direction, given the reciprocal of that
+/// direction. A ray starting inside the box enters it at 0.
+pub(crate) fn ray_box(origin: Vec3d, inv_dir: Vec3d, bbox: &BoundingBox) -> Option<f32> {
+    let (mut near, mut far) = (0.0_f32, f32::INFINITY);
+    for (origin, inv_dir, min, max) in [
+        (origin.x, inv_dir.x, bbox.min.x, bbox.max.x),
+        (origin.y, inv_dir.y, bbox.min.y, bbox.max.y),
+        (origin.z, inv_dir.z, bbox.min.z, bbox.max.z),
+    ] {
+        // f32::max and f32::min drop the NaN a ray running along a face of the box produces
+        let (t1, t2) = ((min - origin) * inv_dir, (max - origin) * inv_dir);
+        near = near.max(t1.min(t2));
+        far = far.min(t1.max(t2));
+    }
+    (near <= far).then_some(near)
+}
+
+/// Intersects the ray from `origin` along `dir` with a triangle, either side of it, returning the distance in units of
+/// `dir` and the barycentric weights of the second and third corners.
+pub(crate) fn ray_triangle(origin: Vec3d, dir: Vec3d, tri: [Vec3d; 3]) -> Option<(f32, f32, f32)> {
+    let [a, b, c] = tri.map(dvec);
+    let (origin, dir) = (dvec(origin), dvec(dir));
+    let (edge1, edge2) = (b - a, c - a);
+    let p = dir.cross(&edge2);
+    let det = edge1.dot(&p);
+    // the ray runs along the plane of the triangle, or there's no triangle to speak of
+    if det.abs() <= 1e-12 * edge1.norm() * edge2.norm() * dir.norm() {
+        return None;
+    }
+
+    let s = origin - a;
+    let u = s.dot(&p) / det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(&edge1);
+    let v = dir.dot(&q) / det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let t = edge2.dot(&q) / det;
+    (t >= 0.0).then_some((t as f32, u as f32, v as f32))
+}
+
+/// How far `point` is from the box, 0 if it's inside.
+pub(crate) fn box_distance(point: Vec3d, bbox: &BoundingBox) -> f32 {
+    let outside = |x: f32, min: f32, max: f32| (min - x).max(x - max).max(0.0);
+    Vec3d::new(outside(point.x, bbox.min.x, bbox.max.x), outside(point.y, bbox.min.y, bbox.max.y), outside(point.z, bbox.min.z, bbox.max.z))
+        .magnitude()
+}
+
+/// The point on the triangle nearest to `point`, as the barycentric weights of the second and third corners.
+pub(crate) fn closest_point_on_triangle(point: Vec3d, tri: [Vec3d; 3]) -> (f32, f32) {
+    // going through the corner, edge and face regions in turn, as in Ericson's Real-Time Collision Detection
+    let [a, b, c] = tri.map(dvec);
+    let point = dvec(point);
+    let (ab, ac) = (b - a, c - a);
+
+    let ap = point - a;
+    let (d1, d2) = (ab.dot(&ap), ac.dot(&ap));
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return (0.0, 0.0);
+    }
+
+    let bp = point - b;
+    let (d3, d4) = (ab.dot(&bp), ac.dot(&bp));
+    if d3 >= 0.0 && d4 <= d3 {
+        return (1.0, 0.0);
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return ((d1 / (d1 - d3)) as f32, 0.0);
+    }
+
+    let cp = point - c;
+    let (d5, d6) = (ab.dot(&cp), ac.dot(&cp));
+    if d6 >= 0.0 && d5 <= d6 {
+        return (0.0, 1.0);
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return (0.0, (d2 / (d2 - d6)) as f32);
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        let w = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+        return ((1.0 - w) as f32, w as f32);
+    }
+
+    let sum = va + vb + vc;
+    if sum <= 0.0 {
+        // no area, and none of the above caught it, so it's no more than a point
+        return (0.0, 0.0);
+    }
+    ((vb / sum) as f32, (vc / sum) as f32)
+}
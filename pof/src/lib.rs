@@ -1,11 +1,37 @@
 // #![warn(missing_docs)]
 #![allow(clippy::useless_format)]
 
+mod geometry;
 mod parse;
+mod properties;
 mod types;
 mod write;
 
+pub use parse::inspect;
+pub use parse::parse_bytes;
 pub use parse::parse_dae;
 pub use parse::parse_gltf;
+pub use parse::BspBlockLayout;
+pub use parse::ChunkComparison;
+pub use parse::ChunkLayout;
+pub use parse::ChunkMatch;
+pub use parse::ImportDiagnostic;
+pub use parse::ImportError;
+pub use parse::ImportReport;
+pub use parse::ParseDiagnostic;
+pub use parse::ParseError;
+pub use parse::ParseOptions;
 pub use parse::Parser;
+pub use parse::PofLayout;
+pub use parse::RoundTripReport;
+pub use parse::SubObjectLayout;
+pub use properties::Properties;
+pub use properties::PropertiesKind;
+pub use properties::Property;
+pub use properties::PropertyDef;
+pub use properties::PropertyIssue;
+pub use properties::PropertyType;
+pub use properties::PropertyValue;
 pub use types::*;
+pub use write::WriteError;
+pub use write::WriteOptions;
@@ -1,79 +1,194 @@
 // #![warn(missing_docs)]
 #![allow(clippy::useless_format)]
 
-mod parse;
-mod types;
-mod write;
-
-pub use parse::parse_dae;
-pub use parse::Parser;
-pub use types::*;
-
+use pof::{inspect, BspData, BspStrategy, ChunkMatch, ParseError, Parser, PofLayout, RoundTripReport, ShieldData, TreeQuality};
+use std::collections::BTreeMap;
 use std::fs::File;
-use std::io::{self, Write};
+use std::io::BufReader;
+use std::path::Path;
+use std::process::ExitCode;
 
 use walkdir::WalkDir;
 
-fn main() -> io::Result<()> {
-    // D:\\Freespace\\Knossos\\library\\FS2\\blueplanetcomplete-1.1.5\\data\\models\\avenger3.pof
-    // has a bsp leaf with no polies?
-
-    // for (i, entry) in WalkDir::new("D:\\Freespace\\Knossos\\library\\FS2").into_iter().enumerate().skip(500) {
-    //     let entry = entry.unwrap();
-    //     let filename: &str = entry.file_name().to_str().unwrap();
-    //     if filename.ends_with(".pof") {
-    //         println!("{}: Testing model {:#?}...", i, entry.path());
-    //         let file = File::open(entry.path()).unwrap();
-    //         let mut parser = Parser::new(file)?;
-    //         let model = parser.parse(entry.path().to_path_buf())?;
-
-    //         let mut file = File::create("output.pof").unwrap();
-    //         model.write(&mut file).unwrap();
-    //         let buf1 = std::fs::read("output.pof").unwrap();
-
-    //         let file = File::open("output.pof").unwrap();
-    //         let mut parser = Parser::new(file)?;
-    //         let model = parser.parse(entry.path().to_path_buf())?;
-
-    //         let mut file = File::create("output2.pof").unwrap();
-    //         model.write(&mut file).unwrap();
-    //         let buf2 = std::fs::read("output2.pof").unwrap();
-
-    //         //assert!(buf1 == buf2);
-    //         if buf1 != buf2 {
-    //             println!("Buffers weren't equal!");
-    //         }
-    //     }
-    // }
-
-    // use std::path::PathBuf;
-    // let path = PathBuf::from("D:\\Freespace\\Knossos\\library\\FS2\\blueplanetcomplete-1.1.5\\data\\models\\Cargo_Platform.pof");
-    // let file = File::open(path.clone()).unwrap();
-    // let mut parser = Parser::new(file)?;
-    // let model = parser.parse(path.clone())?;
-
-    // let mut file = File::create("output1.log").unwrap();
-    // write!(file, "{:#?}", model);
-
-    // let mut file = File::create("output.pof").unwrap();
-    // model.write(&mut file).unwrap();
-    // let buf1 = std::fs::read("output.pof").unwrap();
-
-    // let file = File::open("output.pof").unwrap();
-    // let mut parser = Parser::new(file)?;
-    // let model = parser.parse(path)?;
-
-    // let mut file = File::create("output2.log").unwrap();
-    // write!(file, "{:#?}", model);
-
-    // let mut file = File::create("output2.pof").unwrap();
-    // model.write(&mut file).unwrap();
-    // let buf2 = std::fs::read("output2.pof").unwrap();
-
-    // //assert!(buf1 == buf2);
-    // if buf1 != buf2 {
-    //     println!("Buffers weren't equal!");
-    // }
+const USAGE: &str = "\
+usage: pof <command> [args]
+
+commands:
+    dump <file> [--blocks]    print the chunks of a POF file and the BSP blocks of each subobject
+                              (--blocks lists every BSP block rather than a summary per type)
+    roundtrip <dir>           parse and rewrite every POF under a directory, reporting any file or chunk
+                              which doesn't come back out byte-identical, and any which isn't stable
+                              when round tripped a second time
+    bsp <file>                compare the collision trees of each subobject and the shield as stored in
+                              a POF file against those built by each BSP strategy";
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+
+    let result = match args[..] {
+        ["dump", file] => dump(Path::new(file), false),
+        ["dump", file, "--blocks"] | ["dump", "--blocks", file] => dump(Path::new(file), true),
+        ["roundtrip", dir] => roundtrip(Path::new(dir)),
+        ["bsp", file] => bsp(Path::new(file)),
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn dump(path: &Path, show_blocks: bool) -> Result<(), ParseError> {
+    let layout = inspect(BufReader::new(File::open(path)?))?;
+    print_layout(path, &layout, show_blocks);
+    Ok(())
+}
+
+fn print_layout(path: &Path, layout: &PofLayout, show_blocks: bool) {
+    const INDENT: &str = "                          ";
+
+    println!("{}: version {}, {} chunks", path.display(), layout.version, layout.chunks.len());
+    println!("{:>10}  {:4}  {:>10}", "offset", "id", "length");
+    for chunk in &layout.chunks {
+        print!("{:>10}  {:4}  {:>10}", chunk.offset, chunk.id_str(), chunk.len);
+        match &chunk.subobject {
+            Some(subobj) => println!("  subobject {} \"{}\"", subobj.obj_id, subobj.name),
+            None => println!(),
+        }
+
+        if let Some(subobj) = &chunk.subobject {
+            println!("{}BSP data at offset {}, {} bytes, {} blocks", INDENT, subobj.bsp_offset, subobj.bsp_len, subobj.blocks.len());
+            if show_blocks {
+                for block in &subobj.blocks {
+                    println!("{}  {:>10}  {:<11}  {:>10}", INDENT, block.offset, block.kind_name(), block.size);
+                }
+            } else {
+                // kind -> (name, count, total size)
+                let mut summary = BTreeMap::new();
+                for block in &subobj.blocks {
+                    let entry = summary.entry(block.kind).or_insert((block.kind_name(), 0, 0));
+                    entry.1 += 1;
+                    entry.2 += block.size as u64;
+                }
+                for (name, count, total_size) in summary.values() {
+                    println!("{}  {:<11}  {:>6} x  {:>10} bytes", INDENT, name, count, total_size);
+                }
+            }
+        }
+
+        if let Some(problem) = &chunk.problem {
+            println!("{}!! {}", INDENT, problem);
+        }
+    }
+}
 
+fn roundtrip(dir: &Path) -> Result<(), ParseError> {
+    let (mut identical, mut different, mut unstable, mut failed) = (0, 0, 0, 0);
+
+    for entry in WalkDir::new(dir) {
+        let entry = entry.map_err(std::io::Error::from)?;
+        let path = entry.path();
+        if !entry.file_type().is_file() || !path.extension().map_or(false, |ext| ext.eq_ignore_ascii_case("pof")) {
+            continue;
+        }
+
+        let result = std::fs::read(path)
+            .map_err(ParseError::from)
+            .and_then(|original| Parser::new(&original[..])?.parse(path.to_path_buf())?.verify_round_trip(&original));
+        match result {
+            Ok(report) => {
+                print_round_trip(path, &report);
+                if report.is_identical() {
+                    identical += 1;
+                } else {
+                    different += 1;
+                }
+                if !report.is_stable() {
+                    unstable += 1;
+                }
+            }
+            Err(err) => {
+                println!("FAILED     {}: {}", path.display(), err);
+                failed += 1;
+            }
+        }
+    }
+
+    println!();
+    println!("{} files: {} identical, {} different, {} unstable, {} failed", identical + different + failed, identical, different, unstable, failed);
+    if unstable + failed > 0 {
+        return Err(std::io::Error::other("some files did not round trip safely").into());
+    }
     Ok(())
 }
+
+fn print_round_trip(path: &Path, report: &RoundTripReport) {
+    match report.first_difference {
+        None => println!("identical  {}", path.display()),
+        Some(offset) => println!("different  {}: first difference at byte {}", path.display(), offset),
+    }
+
+    for chunk in &report.chunks {
+        let location = |place: Option<(u64, u32)>| place.map_or(format!("-"), |(offset, len)| format!("{} ({} bytes)", offset, len));
+        let result = match chunk.result {
+            ChunkMatch::Identical => continue,
+            ChunkMatch::Differs(offset) => format!("differs at chunk byte {}", offset),
+            ChunkMatch::Dropped => format!("dropped"),
+            ChunkMatch::Added => format!("added"),
+        };
+        println!("    {:4}  original {:<20}  written {:<20}  {}", chunk.id_str(), location(chunk.original), location(chunk.written), result);
+    }
+
+    if !report.is_identical() && report.chunks.iter().all(|chunk| chunk.result == ChunkMatch::Identical) {
+        println!("    all chunks are identical, only their order differs");
+    }
+
+    if let Some(offset) = report.unstable_at {
+        println!("    UNSTABLE: a second round trip differs from the first at byte {}", offset);
+    }
+}
+
+fn bsp(path: &Path) -> Result<(), ParseError> {
+    const STRATEGIES: [(&str, BspStrategy); 2] = [("median", BspStrategy::Median), ("surface area", BspStrategy::SurfaceArea)];
+
+    let model = Parser::new(BufReader::new(File::open(path)?))?.parse(path.to_path_buf())?;
+    println!("{}: version {}", path.display(), model.version);
+    println!("{:14}  {:>9}  {:>9}  {:>8}  {:>12}", "tree", "max depth", "avg depth", "leaves", "bbox volume");
+
+    for subobj in model.sub_objects.iter() {
+        let bsp_data = &subobj.bsp_data;
+        println!("subobject {} \"{}\"", subobj.obj_id.0, subobj.name);
+        print_quality("as stored", &bsp_data.collision_tree.quality());
+        for (name, strategy) in STRATEGIES {
+            let polygons = bsp_data.collision_tree.leaves().map(|(_, poly)| poly.clone());
+            print_quality(name, &BspData::recalculate_with_strategy(&bsp_data.verts, polygons, strategy).quality());
+        }
+    }
+
+    if let Some(shield) = &model.shield_data {
+        println!("shield");
+        if let Some(tree) = &shield.collision_tree {
+            print_quality("as stored", &tree.quality());
+        }
+        for (name, strategy) in STRATEGIES {
+            print_quality(name, &ShieldData::recalculate_tree_with_strategy(&shield.verts, &shield.polygons, strategy).quality());
+        }
+    }
+    Ok(())
+}
+
+fn print_quality(name: &str, quality: &TreeQuality) {
+    // the volume is relative to the root's, so subobjects of different sizes are comparable
+    println!(
+        "  {:12}  {:>9}  {:>9.2}  {:>8}  {:>11.2}x",
+        name, quality.max_depth, quality.average_depth, quality.leaf_count, quality.relative_bbox_volume
+    );
+}
@@ -1,17 +1,308 @@
 use crate::*;
 use byteorder::{ReadBytesExt, LE};
-use core::panic;
 use dae_parser as dae;
 use glm::Mat4x4;
 use log::warn;
 use nalgebra_glm as glm;
 use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
+use std::fmt::{Debug, Display};
 use std::fs::File;
-use std::io::{self, BufReader, ErrorKind, Read, Seek, SeekFrom};
+use std::io::{self, BufReader, ErrorKind, Read};
 use std::path::PathBuf;
 
+/// The ways in which reading a POF file can fail.
+///
+/// Chunk-level failures carry the id of the offending chunk and the byte offset of its header within the file.
+pub enum ParseError {
+    /// The file does not start with `PSPO`
+    BadMagic(ChunkId),
+    /// The version number following the magic is not one we know how to read
+    UnsupportedVersion(i32),
+    /// A chunk which may only appear once was found again
+    DuplicateChunk { id: ChunkId, offset: u64 },
+    /// A required chunk never appeared, or a chunk appeared without the chunk it depends on
+    MissingChunk(ChunkId),
+    /// A subobject id was declared in the header but no subobject chunk ever filled it in
+    MissingSubObject(ObjectId),
+    /// A turret chunk refers to a base or gun subobject which has not been defined (yet)
+    TurretMissingSubObject { id: ChunkId, offset: u64, obj_id: ObjectId },
+    /// The file ended in the middle of this chunk
+    TruncatedChunk { id: ChunkId, offset: u64 },
+    /// The BSP data (or shield collision tree) in this chunk could not be read
+    MalformedBsp { id: ChunkId, offset: u64, reason: String },
+    /// The chunk was fully read, but contains something invalid
+    InvalidChunk { id: ChunkId, offset: u64, reason: String },
+    /// Any other I/O error from the underlying reader
+    Io(io::Error),
+}
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::BadMagic(magic) => write!(f, "not a POF file (expected PSPO, found {:x?})", magic),
+            ParseError::UnsupportedVersion(version) => write!(f, "unsupported POF version {}", version),
+            ParseError::DuplicateChunk { id, offset } => write!(f, "duplicate {} chunk at offset {}", chunk_id_str(id), offset),
+            ParseError::MissingChunk(id) => write!(f, "no {} chunk found", chunk_id_str(id)),
+            ParseError::MissingSubObject(obj_id) => write!(f, "subobject {} is declared in the header but was never defined", obj_id.0),
+            ParseError::TurretMissingSubObject { id, offset, obj_id } => {
+                write!(f, "{} chunk at offset {} refers to undefined subobject {}", chunk_id_str(id), offset, obj_id.0)
+            }
+            ParseError::TruncatedChunk { id, offset } => write!(f, "{} chunk at offset {} is truncated", chunk_id_str(id), offset),
+            ParseError::MalformedBsp { id, offset, reason } => {
+                write!(f, "malformed BSP data in {} chunk at offset {}: {}", chunk_id_str(id), offset, reason)
+            }
+            ParseError::InvalidChunk { id, offset, reason } => write!(f, "invalid {} chunk at offset {}: {}", chunk_id_str(id), offset, reason),
+            ParseError::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+impl Debug for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+impl ParseError {
+    /// What went wrong, leaving out which chunk it happened in
+    fn reason(&self) -> String {
+        match self {
+            ParseError::DuplicateChunk { .. } => format!("duplicate chunk"),
+            ParseError::TurretMissingSubObject { obj_id, .. } => format!("turret refers to missing subobject {}", obj_id.0),
+            ParseError::TruncatedChunk { .. } => format!("chunk is truncated"),
+            ParseError::MalformedBsp { reason, .. } => format!("malformed BSP data: {}", reason),
+            ParseError::InvalidChunk { reason, .. } => reason.clone(),
+            err => err.to_string(),
+        }
+    }
+}
+impl From<io::Error> for ParseError {
+    fn from(err: io::Error) -> Self {
+        ParseError::Io(err)
+    }
+}
+impl From<ParseError> for io::Error {
+    fn from(err: ParseError) -> Self {
+        match err {
+            ParseError::Io(err) => err,
+            err => io::Error::new(ErrorKind::InvalidData, err),
+        }
+    }
+}
+
+/// The physical layout of a POF file, as found by [`inspect`]
+#[derive(Debug, Clone)]
+pub struct PofLayout {
+    pub version: Version,
+    /// every chunk in file order, including any the parser wouldn't recognize
+    pub chunks: Vec<ChunkLayout>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChunkLayout {
+    pub id: ChunkId,
+    /// offset of the chunk header within the file
+    pub offset: u64,
+    /// length of the chunk contents, not including the 8 byte header
+    pub len: u32,
+    /// for `OBJ2`/`SOBJ` chunks, what could be made of the subobject inside
+    pub subobject: Option<SubObjectLayout>,
+    /// set if the chunk is damaged in some way; the walk stops at a truncated chunk
+    pub problem: Option<String>,
+}
+impl ChunkLayout {
+    pub fn id_str(&self) -> String {
+        chunk_id_str(&self.id)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SubObjectLayout {
+    pub obj_id: u32,
+    pub name: String,
+    /// offset of the BSP data within the file
+    pub bsp_offset: u64,
+    pub bsp_len: u32,
+    /// the BSP blocks reachable from the start of the BSP data, in file order
+    pub blocks: Vec<BspBlockLayout>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BspBlockLayout {
+    /// the block type, e.g. `DEFFPOINTS` or `SORTNORM2`
+    pub kind: u32,
+    /// offset of the block within the BSP data
+    pub offset: u32,
+    /// the size recorded in the block header
+    pub size: u32,
+}
+impl BspBlockLayout {
+    pub fn kind_name(&self) -> &'static str {
+        match self.kind {
+            BspData::ENDOFBRANCH => "ENDOFBRANCH",
+            BspData::DEFFPOINTS => "DEFFPOINTS",
+            BspData::FLATPOLY => "FLATPOLY",
+            BspData::TMAPPOLY => "TMAPPOLY",
+            BspData::SORTNORM => "SORTNORM",
+            BspData::BOUNDBOX => "BOUNDBOX",
+            BspData::TMAPPOLY2 => "TMAPPOLY2",
+            BspData::SORTNORM2 => "SORTNORM2",
+            _ => "unknown",
+        }
+    }
+}
+
+/// The result of [`Model::verify_round_trip`]
+#[derive(Debug, Clone)]
+pub struct RoundTripReport {
+    /// offset of the first byte at which the written file differs from the original, None if they're identical
+    pub first_difference: Option<u64>,
+    /// the chunks of both files, paired up by id and order of appearance
+    pub chunks: Vec<ChunkComparison>,
+    /// offset of the first byte at which writing the model a second time differs from the first, None if the round trip is stable
+    pub unstable_at: Option<u64>,
+}
+impl RoundTripReport {
+    pub fn is_identical(&self) -> bool {
+        self.first_difference.is_none()
+    }
+
+    pub fn is_stable(&self) -> bool {
+        self.unstable_at.is_none()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ChunkComparison {
+    pub id: ChunkId,
+    /// offset and length of the chunk in the original file
+    pub original: Option<(u64, u32)>,
+    /// offset and length of the chunk in the written file
+    pub written: Option<(u64, u32)>,
+    pub result: ChunkMatch,
+}
+impl ChunkComparison {
+    pub fn id_str(&self) -> String {
+        chunk_id_str(&self.id)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkMatch {
+    Identical,
+    /// the chunks differ, first at this offset from the start of the chunk header
+    Differs(u64),
+    /// the chunk was in the original file but wasn't written
+    Dropped,
+    /// the chunk wasn't in the original file
+    Added,
+}
+
+/// How far a subobject id may run ahead of the subobjects found so far, so that a garbage id or header count can't make the parser
+/// allocate billions of slots
+const MAX_SUBOBJECT_ID_GAP: usize = 1024;
+
+/// Knobs for [`Parser::parse_with_options`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ParseOptions {
+    /// Instead of failing on a damaged chunk, skip or repair it and carry on, salvaging as much of the model as possible.
+    /// Every such recovery is recorded as a [`ParseDiagnostic`].
+    pub lenient: bool,
+}
+
+/// A record of something the parser had to repair or throw away to produce a usable model
+#[derive(Debug, Clone)]
+pub struct ParseDiagnostic {
+    /// the chunk this concerns and the offset of its header, if it can be pinned to one
+    pub chunk: Option<(ChunkId, u64)>,
+    pub message: String,
+}
+impl ParseDiagnostic {
+    fn record(diagnostics: &mut Vec<ParseDiagnostic>, chunk: Option<(ChunkId, u64)>, message: String) {
+        let diagnostic = ParseDiagnostic { chunk, message };
+        warn!("{}", diagnostic);
+        diagnostics.push(diagnostic);
+    }
+}
+impl Display for ParseDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.chunk {
+            Some((id, offset)) => write!(f, "{} chunk at offset {}: {}", chunk_id_str(id), offset, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+fn invalid_data(msg: impl Into<String>) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, msg.into())
+}
+
 impl Model {
+    /// Checks whether writing this model reproduces `original`, the file it was parsed from, byte for byte, both as a whole and chunk by chunk.
+    /// Also checks that parsing the written file and writing it again gives the same bytes, i.e. that the round trip is stable.
+    pub fn verify_round_trip(&self, original: &[u8]) -> Result<RoundTripReport, ParseError> {
+        fn chunk_bytes<'a>(buf: &'a [u8], chunk: &ChunkLayout) -> &'a [u8] {
+            let start = (chunk.offset as usize).min(buf.len());
+            let end = (chunk.offset as usize + 8 + chunk.len as usize).min(buf.len());
+            &buf[start..end]
+        }
+
+        let mut written = vec![];
+        self.write(&mut written)?;
+
+        let reparsed = Parser::new(&written[..])?.parse(self.path_to_file.clone())?;
+        let mut rewritten = vec![];
+        reparsed.write(&mut rewritten)?;
+
+        let original_layout = inspect(original)?;
+        let written_layout = inspect(&written[..])?;
+
+        // pair up the nth chunk of each id in the original with the nth of that id in the output, so reordered chunks still match
+        let mut written_by_id: HashMap<ChunkId, Vec<&ChunkLayout>> = HashMap::new();
+        for chunk in written_layout.chunks.iter().rev() {
+            written_by_id.entry(chunk.id).or_default().push(chunk);
+        }
+
+        let mut chunks = vec![];
+        for chunk in &original_layout.chunks {
+            let written_chunk = written_by_id.get_mut(&chunk.id).and_then(Vec::pop);
+            chunks.push(ChunkComparison {
+                id: chunk.id,
+                original: Some((chunk.offset, chunk.len)),
+                written: written_chunk.map(|written_chunk| (written_chunk.offset, written_chunk.len)),
+                result: match written_chunk {
+                    Some(written_chunk) => match first_difference(chunk_bytes(original, chunk), chunk_bytes(&written, written_chunk)) {
+                        Some(offset) => ChunkMatch::Differs(offset),
+                        None => ChunkMatch::Identical,
+                    },
+                    None => ChunkMatch::Dropped,
+                },
+            });
+        }
+        for chunk in &written_layout.chunks {
+            if written_by_id[&chunk.id].iter().any(|unpaired| unpaired.offset == chunk.offset) {
+                chunks.push(ChunkComparison {
+                    id: chunk.id,
+                    original: None,
+                    written: Some((chunk.offset, chunk.len)),
+                    result: ChunkMatch::Added,
+                });
+            }
+        }
+
+        Ok(RoundTripReport {
+            first_difference: first_difference(original, &written),
+            chunks,
+            unstable_at: first_difference(&written, &rewritten),
+        })
+    }
+
     fn prune_unused_textures(&mut self) {
         // remove unused textures
         // tally up used texture ids
@@ -40,23 +331,60 @@ impl Model {
     }
 }
 
+/// Walks the chunks of a POF file, and the BSP blocks within each subobject, without building a [`Model`]
+pub fn inspect<R: Read>(file: R) -> Result<PofLayout, ParseError> {
+    Parser::new(file)?.inspect()
+}
+
+/// Parses a POF file that is already in memory, such as one pulled out of an archive
+pub fn parse_bytes(bytes: &[u8]) -> Result<Model, ParseError> {
+    Parser::new(bytes)?.parse(PathBuf::new())
+}
+
+// counts the bytes read so far, so chunk offsets can be reported without needing the reader to be `Seek`
+struct OffsetReader<R> {
+    inner: R,
+    position: u64,
+}
+impl<R: Read> Read for OffsetReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+/// Parses a POF file from any reader, reading it strictly front to back, so streams that can't seek work just as well as files
 pub struct Parser<R> {
-    file: R,
+    file: OffsetReader<R>,
     version: Version,
 }
-impl<R: Read + Seek> Parser<R> {
-    pub fn new(mut file: R) -> io::Result<Parser<R>> {
-        assert!(&read_bytes(&mut file)? == b"PSPO", "Not a freespace 2 pof file!");
+impl<R: Read> Parser<R> {
+    pub fn new(file: R) -> Result<Parser<R>, ParseError> {
+        let mut file = OffsetReader { inner: file, position: 0 };
+        let magic = read_bytes(&mut file)?;
+        if &magic != b"PSPO" {
+            return Err(ParseError::BadMagic(magic));
+        }
 
-        let version: Version = read_i32(&mut file)?.try_into().expect("Unrecognized pof version");
+        let version = read_i32(&mut file)?;
+        let version: Version = version.try_into().map_err(|()| ParseError::UnsupportedVersion(version))?;
 
         // println!("The verison is {:?}", version);
 
         Ok(Parser { file, version })
     }
 
-    pub fn parse(&mut self, path: PathBuf) -> io::Result<Model> {
+    pub fn parse(&mut self, path: PathBuf) -> Result<Model, ParseError> {
+        self.parse_with_options(path, &ParseOptions::default()).map(|(model, _)| model)
+    }
+
+    /// Parses the model, also returning a list of everything that had to be repaired or discarded along the way.
+    ///
+    /// In lenient mode damaged chunks are skipped rather than failing the whole parse, so a broken file can still be opened and fixed up.
+    pub fn parse_with_options(&mut self, path: PathBuf, options: &ParseOptions) -> Result<(Model, Vec<ParseDiagnostic>), ParseError> {
         // println!("parsing new model!");
+        let mut diagnostics = vec![];
         let mut header = None;
         let mut sub_objects = vec![];
         let mut textures = None;
@@ -76,390 +404,536 @@ impl<R: Read + Seek> Parser<R> {
 
         let mut shield_tree_chunk = None;
         let mut debris_objs = vec![];
+        let mut unknown_chunks = vec![];
+
+        let mut shield_tree_offset = 0;
+        let mut bsp_data_buffers = vec![];
 
         loop {
+            let chunk_offset = self.file.position;
             let id = &match self.read_bytes() {
                 Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
                 id_result => id_result?,
             };
-            let len = self.read_i32()?;
-
-            // println!("found chunk {}", std::str::from_utf8(id).unwrap());
-            // println!("length is {} bytes", len);
-            match id {
-                b"OHDR" | b"HDR2" => {
-                    assert!(header.is_none());
-                    assert_eq!(self.version >= Version::V21_16, id == b"HDR2");
-
-                    let (max_radius, obj_flags, num_subobjects);
-                    if self.version >= Version::V21_16 {
-                        max_radius = self.read_f32()?;
-                        obj_flags = self.read_u32()?;
-                        num_subobjects = self.read_u32()?;
-                    } else {
-                        num_subobjects = self.read_u32()?;
-                        max_radius = self.read_f32()?;
-                        obj_flags = self.read_u32()?;
-                    }
 
-                    sub_objects = vec![None; num_subobjects as usize];
+            // read the whole chunk up front, so a malformed chunk can't drag its neighbours down with it
+            // and skipping it doesn't need the reader to seek
+            let data = match self.read_i32().and_then(|len| {
+                let len = usize::try_from(len).map_err(|_| invalid_data(format!("negative chunk length {}", len)))?;
+                self.read_exact_vec(len)
+            }) {
+                Ok(data) => data,
+                Err(err) => {
+                    let err = match err.kind() {
+                        ErrorKind::UnexpectedEof => ParseError::TruncatedChunk { id: *id, offset: chunk_offset },
+                        ErrorKind::InvalidData => ParseError::InvalidChunk { id: *id, offset: chunk_offset, reason: err.to_string() },
+                        _ => err.into(),
+                    };
+                    if !options.lenient || matches!(err, ParseError::Io(_)) {
+                        return Err(err);
+                    }
+                    ParseDiagnostic::record(
+                        &mut diagnostics,
+                        Some((*id, chunk_offset)),
+                        format!("{}; the rest of the file was discarded", err.reason()),
+                    );
+                    break;
+                }
+            };
+            let mut chunk = Parser {
+                file: OffsetReader { inner: &data[..], position: chunk_offset + 8 },
+                version: self.version,
+            };
 
-                    let bounding_box = self.read_bbox()?;
+            let chunk_result = (|| -> Result<(), ParseError> {
+                macro_rules! invalid {
+                    ($($arg:tt)*) => {
+                        return Err(ParseError::InvalidChunk { id: *id, offset: chunk_offset, reason: format!($($arg)*) })
+                    };
+                }
+                macro_rules! ensure_unique {
+                    ($chunk:expr) => {
+                        if $chunk.is_some() {
+                            return Err(ParseError::DuplicateChunk { id: *id, offset: chunk_offset });
+                        }
+                    };
+                }
 
-                    let detail_levels = self.read_list(|this| Ok(ObjectId(this.read_u32()?)))?;
-                    debris_objs = self.read_list(|this| Ok(ObjectId(this.read_u32()?)))?;
+                // println!("found chunk {}", std::str::from_utf8(id).unwrap());
+                // println!("length is {} bytes", data.len());
+                match id {
+                    b"OHDR" | b"HDR2" => {
+                        ensure_unique!(header);
+                        if (self.version >= Version::V21_16) != (id == b"HDR2") {
+                            invalid!("{} chunk is not valid in version {}", chunk_id_str(id), self.version);
+                        }
 
-                    let (mut mass, center_of_mass, mut moment_of_inertia);
-                    if self.version >= Version::V19_03 {
-                        mass = self.read_f32()?;
-                        center_of_mass = self.read_vec3d()?;
-                        moment_of_inertia = Mat3d {
-                            rvec: self.read_vec3d()?,
-                            uvec: self.read_vec3d()?,
-                            fvec: self.read_vec3d()?,
-                        };
-                        if self.version < Version::V20_09 {
-                            // migration code ported from FSO
-                            let area_mass = mass.powf(0.6667) * 4.65;
-                            moment_of_inertia *= mass / area_mass;
-                            mass = area_mass;
+                        let (max_radius, obj_flags, num_subobjects);
+                        if self.version >= Version::V21_16 {
+                            max_radius = chunk.read_f32()?;
+                            obj_flags = chunk.read_u32()?;
+                            num_subobjects = chunk.read_u32()?;
+                        } else {
+                            num_subobjects = chunk.read_u32()?;
+                            max_radius = chunk.read_f32()?;
+                            obj_flags = chunk.read_u32()?;
                         }
-                    } else {
-                        mass = 50.0; // default used by FSO
-                        center_of_mass = Vec3d::ZERO;
-                        moment_of_inertia = Mat3d::IDENTITY;
-                        moment_of_inertia *= 0.001;
-                    };
 
-                    let cross_sections = if self.version >= Version::V20_14 {
-                        let num_cross_sections = match self.read_u32()? {
-                            u32::MAX => 0,
-                            n => n,
+                        let bounding_box = chunk.read_bbox()?;
+
+                        let detail_levels = chunk.read_list(|this| Ok(ObjectId(this.read_u32()?)))?;
+                        debris_objs = chunk.read_list(|this| Ok(ObjectId(this.read_u32()?)))?;
+
+                        let (mut mass, center_of_mass, mut moment_of_inertia);
+                        if self.version >= Version::V19_03 {
+                            mass = chunk.read_f32()?;
+                            center_of_mass = chunk.read_vec3d()?;
+                            moment_of_inertia = Mat3d {
+                                rvec: chunk.read_vec3d()?,
+                                uvec: chunk.read_vec3d()?,
+                                fvec: chunk.read_vec3d()?,
+                            };
+                            if self.version < Version::V20_09 {
+                                // migration code ported from FSO
+                                let area_mass = mass.powf(0.6667) * 4.65;
+                                moment_of_inertia *= mass / area_mass;
+                                mass = area_mass;
+                            }
+                        } else {
+                            mass = 50.0; // default used by FSO
+                            center_of_mass = Vec3d::ZERO;
+                            moment_of_inertia = Mat3d::IDENTITY;
+                            moment_of_inertia *= 0.001;
                         };
-                        self.read_list_n(num_cross_sections as usize, |this| Ok((this.read_f32()?, this.read_f32()?)))?
-                    } else {
-                        vec![]
-                    };
 
-                    let bsp_lights = if self.version >= Version::V20_07 {
-                        self.read_list(|this| {
-                            Ok(BspLight {
-                                location: this.read_vec3d()?,
-                                kind: match this.read_u32()? {
-                                    1 => BspLightKind::Muzzle,
-                                    2 => BspLightKind::Thruster,
-                                    _ => panic!(), // maybe dont just panic
-                                },
-                            })
-                        })?
-                    } else {
-                        vec![]
-                    };
-
-                    header = Some(ObjHeader {
-                        num_subobjects,
-                        max_radius,
-                        obj_flags,
-                        bbox: bounding_box,
-                        detail_levels,
-                        mass,
-                        center_of_mass,
-                        moment_of_inertia,
-                        cross_sections,
-                        bsp_lights,
-                    });
-                    //println!("{:#?}", header)
-                }
-                b"SOBJ" | b"OBJ2" => {
-                    assert!(header.is_some());
-                    assert_eq!(self.version >= Version::V21_16, id == b"OBJ2");
+                        let cross_sections = if self.version >= Version::V20_14 {
+                            let num_cross_sections = match chunk.read_u32()? {
+                                u32::MAX => 0,
+                                n => n,
+                            };
+                            chunk.read_list_n(num_cross_sections as usize, |this| Ok((this.read_f32()?, this.read_f32()?)))?
+                        } else {
+                            vec![]
+                        };
 
-                    let obj_id = ObjectId(self.read_u32()?); //id
+                        let bsp_lights = if self.version >= Version::V20_07 {
+                            chunk.read_list(|this| {
+                                Ok(BspLight {
+                                    location: this.read_vec3d()?,
+                                    kind: match this.read_u32()? {
+                                        1 => BspLightKind::Muzzle,
+                                        2 => BspLightKind::Thruster,
+                                        kind => return Err(invalid_data(format!("unknown BSP light kind {}", kind))),
+                                    },
+                                })
+                            })?
+                        } else {
+                            vec![]
+                        };
 
-                    let (radius, parent, offset);
-                    if self.version >= Version::V21_16 {
-                        radius = self.read_f32()?;
-                        parent = self.read_u32()?;
-                        offset = self.read_vec3d()?;
-                    } else {
-                        parent = self.read_u32()?;
-                        offset = self.read_vec3d()?;
-                        radius = self.read_f32()?;
+                        header = Some(ObjHeader {
+                            num_subobjects,
+                            max_radius,
+                            obj_flags,
+                            bbox: bounding_box,
+                            detail_levels,
+                            mass,
+                            center_of_mass,
+                            moment_of_inertia,
+                            cross_sections,
+                            bsp_lights,
+                        });
+                        //println!("{:#?}", header)
                     }
-                    let parent = if parent == u32::MAX {
-                        None
-                    } else {
-                        // assert!(sub_objects[parent as usize].is_some(), "parent out of order");
-                        Some(ObjectId(parent))
-                    };
+                    b"SOBJ" | b"OBJ2" => {
+                        if header.is_none() && !options.lenient {
+                            invalid!("subobject chunk precedes the header");
+                        }
+                        if (self.version >= Version::V21_16) != (id == b"OBJ2") {
+                            invalid!("{} chunk is not valid in version {}", chunk_id_str(id), self.version);
+                        }
 
-                    let geo_center = self.read_vec3d()?;
-                    let bbox = self.read_bbox()?;
-                    let name = self.read_string()?;
-                    let properties = self.read_string()?;
-                    let rotation_type = self.read_i32()?.try_into().unwrap_or_default();
-                    let mut rotation_axis = self.read_i32()?.try_into().unwrap_or_default();
-                    if rotation_type == SubsysRotationType::None {
-                        rotation_axis = SubsysRotationAxis::None
-                    }
+                        let obj_id = ObjectId(chunk.read_u32()?); //id
+                        let declared = header.as_ref().map_or(0, |header| header.num_subobjects as usize);
+                        if obj_id.0 as usize >= sub_objects.len() {
+                            if !options.lenient && obj_id.0 as usize >= declared {
+                                invalid!("subobject id {} is out of range, the header declares {} subobjects", obj_id.0, declared);
+                            }
+                            // slots are only added as the subobjects turn up, and the id must be sane,
+                            // so we don't allocate billions of slots over a garbage value
+                            if obj_id.0 as usize >= sub_objects.len() + MAX_SUBOBJECT_ID_GAP {
+                                invalid!("subobject id {} is too far beyond the {} subobjects found so far", obj_id.0, sub_objects.len());
+                            }
+                            if obj_id.0 as usize >= declared {
+                                ParseDiagnostic::record(
+                                    &mut diagnostics,
+                                    Some((*id, chunk_offset)),
+                                    format!(
+                                        "subobject id {} is beyond the {} subobjects the header declares; slots were added for it",
+                                        obj_id.0, declared
+                                    ),
+                                );
+                            }
+                            sub_objects.resize(obj_id.0 as usize + 1, None);
+                        }
 
-                    let (translation_type, translation_axis) = if self.version >= Version::V23_01 {
-                        let t_type = self.read_i32()?.try_into().unwrap_or_default();
-                        let mut t_axis = self.read_i32()?.try_into().unwrap_or_default();
-                        if t_type == SubsysTranslationType::None {
-                            t_axis = SubsysTranslationAxis::None
+                        let (radius, parent, offset);
+                        if self.version >= Version::V21_16 {
+                            radius = chunk.read_f32()?;
+                            parent = chunk.read_u32()?;
+                            offset = chunk.read_vec3d()?;
+                        } else {
+                            parent = chunk.read_u32()?;
+                            offset = chunk.read_vec3d()?;
+                            radius = chunk.read_f32()?;
                         }
-                        (t_type, t_axis)
-                    } else {
-                        (SubsysTranslationType::None, SubsysTranslationAxis::None)
-                    };
+                        let parent = if parent == u32::MAX {
+                            None
+                        } else if parent as usize >= declared && !options.lenient {
+                            // when lenient, the parent may just not have been seen yet; its checked once all subobjects are in
+                            invalid!("parent id {} of subobject {} is out of range", parent, obj_id.0);
+                        } else {
+                            // assert!(sub_objects[parent as usize].is_some(), "parent out of order");
+                            Some(ObjectId(parent))
+                        };
 
-                    assert!(self.read_i32()? == 0, "chunked models unimplemented in FSO");
-                    let bsp_data_buffer = self.read_byte_buffer()?;
-                    let bsp_data = parse_bsp_data(&bsp_data_buffer, self.version)?;
-                    //println!("parsed subobject {}", name);
+                        let geo_center = chunk.read_vec3d()?;
+                        let bbox = chunk.read_bbox()?;
+                        let name = chunk.read_string()?;
+                        let properties = chunk.read_string()?;
+                        let rotation_type = chunk.read_i32()?.try_into().unwrap_or_default();
+                        let mut rotation_axis = chunk.read_i32()?.try_into().unwrap_or_default();
+                        if rotation_type == SubsysRotationType::None {
+                            rotation_axis = SubsysRotationAxis::None
+                        }
 
-                    assert!(sub_objects[obj_id.0 as usize].is_none());
-                    sub_objects[obj_id.0 as usize] = Some(SubObject {
-                        obj_id,
-                        radius,
-                        parent,
-                        offset,
-                        geo_center,
-                        bbox,
-                        name,
-                        properties,
-                        rotation_type,
-                        rotation_axis,
-                        translation_type,
-                        translation_axis,
-                        bsp_data,
-                        // these rest are to be filled later once we've parsed all the subobjects
-                        ..Default::default()
-                    });
-                    //println!("parsed subobject {:#?}", sub_objects[obj_id.0 as usize]);
-                }
-                b"TXTR" => {
-                    assert!(textures.is_none());
+                        let (translation_type, translation_axis) = if self.version >= Version::V23_01 {
+                            let t_type = chunk.read_i32()?.try_into().unwrap_or_default();
+                            let mut t_axis = chunk.read_i32()?.try_into().unwrap_or_default();
+                            if t_type == SubsysTranslationType::None {
+                                t_axis = SubsysTranslationAxis::None
+                            }
+                            (t_type, t_axis)
+                        } else {
+                            (SubsysTranslationType::None, SubsysTranslationAxis::None)
+                        };
 
-                    textures = Some(self.read_list(|this| this.read_string())?);
-                    //println!("{:#?}", textures);
-                }
-                b"PATH" => {
-                    assert!(paths.is_none());
-
-                    paths = Some(self.read_list(|this| {
-                        Ok(Path {
-                            name: this.read_string()?,
-                            parent: if this.version >= Version::V20_02 {
-                                this.read_string()?
-                            } else {
-                                String::new()
-                            },
-                            points: this.read_list(|this| {
-                                Ok(PathPoint {
-                                    position: this.read_vec3d()?,
-                                    radius: this.read_f32()?,
-                                    turrets: this.read_list(|this| Ok(ObjectId(this.read_u32()?)))?,
-                                })
-                            })?,
-                        })
-                    })?);
-                    //println!("{:#?}", paths);
-                }
-                b"SPCL" => {
-                    assert!(special_points.is_none());
-
-                    special_points = Some(self.read_list(|this| {
-                        Ok(SpecialPoint {
-                            name: this.read_string()?,
-                            properties: this.read_string()?,
-                            position: this.read_vec3d()?,
-                            radius: this.read_f32()?,
-                        })
-                    })?);
-                    //println!("{:#?}", special_points);
-                }
-                b"EYE " => {
-                    eye_points = Some(self.read_list(|this| {
-                        Ok(EyePoint {
-                            attached_subobj: {
-                                let id = this.read_u32()?;
-                                if id == u32::MAX {
-                                    None
+                        if chunk.read_i32()? != 0 {
+                            invalid!("chunked models are unimplemented in FSO");
+                        }
+                        // the BSP data is parsed once all the chunks are in, so every subobject's can be done at once
+                        let bsp_data_buffer = chunk.read_byte_buffer()?;
+                        //println!("parsed subobject {}", name);
+
+                        if sub_objects[obj_id.0 as usize].is_some() {
+                            invalid!("subobject {} is defined more than once", obj_id.0);
+                        }
+                        sub_objects[obj_id.0 as usize] = Some(SubObject {
+                            obj_id,
+                            radius,
+                            parent,
+                            offset,
+                            geo_center,
+                            bbox,
+                            name,
+                            properties,
+                            rotation_type,
+                            rotation_axis,
+                            translation_type,
+                            translation_axis,
+                            // these rest are to be filled later once we've parsed all the subobjects
+                            ..Default::default()
+                        });
+                        bsp_data_buffers.push((obj_id, *id, chunk_offset, bsp_data_buffer));
+                        //println!("parsed subobject {:#?}", sub_objects[obj_id.0 as usize]);
+                    }
+                    b"TXTR" => {
+                        ensure_unique!(textures);
+
+                        textures = Some(chunk.read_list(|this| this.read_string())?);
+                        //println!("{:#?}", textures);
+                    }
+                    b"PATH" => {
+                        ensure_unique!(paths);
+
+                        paths = Some(chunk.read_list(|this| {
+                            Ok(Path {
+                                name: this.read_string()?,
+                                parent: if this.version >= Version::V20_02 {
+                                    this.read_string()?
                                 } else {
-                                    Some(ObjectId(id))
-                                }
-                            },
-                            position: this.read_vec3d()?,
-                            normal: this.read_vec3d()?.try_into().unwrap_or_default(),
-                        })
-                    })?);
-                    //println!("{:#?}", eye_points);
-                }
-                b"GPNT" | b"MPNT" => {
-                    let target = if id == b"GPNT" { &mut primary_weps } else { &mut secondary_weps };
-                    assert!(target.is_none());
-                    *target = Some(self.read_list(|this| {
-                        this.read_list(|this| {
-                            Ok(WeaponHardpoint {
+                                    String::new()
+                                },
+                                points: this.read_list(|this| {
+                                    Ok(PathPoint {
+                                        position: this.read_vec3d()?,
+                                        radius: this.read_f32()?,
+                                        turrets: this.read_list(|this| Ok(ObjectId(this.read_u32()?)))?,
+                                    })
+                                })?,
+                            })
+                        })?);
+                        //println!("{:#?}", paths);
+                    }
+                    b"SPCL" => {
+                        ensure_unique!(special_points);
+
+                        special_points = Some(chunk.read_list(|this| {
+                            Ok(SpecialPoint {
+                                name: this.read_string()?,
+                                properties: this.read_string()?,
                                 position: this.read_vec3d()?,
-                                normal: this.read_vec3d()?.try_into().unwrap_or_default(),
-                                // TODO: document this at https://wiki.hard-light.net/index.php/POF_data_structure
-                                offset: if this.version >= Version::V21_18 && this.version != Version::V22_00 {
-                                    this.read_f32()?
-                                } else {
-                                    0.0
+                                radius: this.read_f32()?,
+                            })
+                        })?);
+                        //println!("{:#?}", special_points);
+                    }
+                    b"EYE " => {
+                        eye_points = Some(chunk.read_list(|this| {
+                            Ok(EyePoint {
+                                attached_subobj: {
+                                    let id = this.read_u32()?;
+                                    if id == u32::MAX {
+                                        None
+                                    } else {
+                                        Some(ObjectId(id))
+                                    }
                                 },
+                                position: this.read_vec3d()?,
+                                normal: this.read_vec3d()?.try_into().unwrap_or_default(),
                             })
-                        })
-                    })?);
-                    //println!("{:#?}", target);
-                }
-                b"TGUN" | b"TMIS" => {
-                    turrets.extend(self.read_list(|this| {
-                        let base_obj = ObjectId(this.read_u32()?);
-                        let gun_obj = ObjectId(this.read_u32()?);
-                        assert!(sub_objects[base_obj.0 as usize].is_some(), "turret precedes base object");
-                        assert!(sub_objects[gun_obj.0 as usize].is_some(), "turret precedes gun object");
-                        Ok(Turret {
-                            base_obj,
-                            gun_obj,
-                            normal: this.read_vec3d()?.try_into().unwrap_or_default(),
-                            fire_points: this.read_list(|this| this.read_vec3d())?,
-                        })
-                    })?);
-                    //println!("{:#?}", turrets);
-                }
-                b"FUEL" => {
-                    assert!(thruster_banks.is_none());
-                    thruster_banks = Some(self.read_list(|this| {
-                        let num_glows = this.read_u32()?;
-                        Ok(ThrusterBank {
-                            properties: if this.version >= Version::V21_17 {
-                                this.read_string()?
-                            } else {
-                                String::new()
-                            },
-                            glows: this.read_list_n(num_glows as usize, |this| {
-                                Ok(ThrusterGlow {
+                        })?);
+                        //println!("{:#?}", eye_points);
+                    }
+                    b"GPNT" | b"MPNT" => {
+                        let target = if id == b"GPNT" { &mut primary_weps } else { &mut secondary_weps };
+                        ensure_unique!(target);
+                        *target = Some(chunk.read_list(|this| {
+                            this.read_list(|this| {
+                                Ok(WeaponHardpoint {
                                     position: this.read_vec3d()?,
                                     normal: this.read_vec3d()?.try_into().unwrap_or_default(),
-                                    // TODO document this at https://wiki.hard-light.net/index.php/POF_data_structure
-                                    radius: if this.version > Version::V20_04 { this.read_f32()? } else { 1.0 },
+                                    // TODO: document this at https://wiki.hard-light.net/index.php/POF_data_structure
+                                    offset: if this.version >= Version::V21_18 && this.version != Version::V22_00 {
+                                        this.read_f32()?
+                                    } else {
+                                        0.0
+                                    },
                                 })
-                            })?,
-                        })
-                    })?);
-                    //println!("{:#?}", thruster_banks);
-                }
-                b"GLOW" => {
-                    assert!(glow_banks.is_none());
-                    glow_banks = Some(self.read_list(|this| {
-                        let num_glow_points;
-                        Ok(GlowPointBank {
-                            disp_time: this.read_i32()?,
-                            on_time: this.read_u32()?,
-                            off_time: this.read_u32()?,
-                            obj_parent: ObjectId(this.read_u32()?),
-                            lod: this.read_u32()?,
-                            glow_type: this.read_u32()?,
-                            properties: {
-                                num_glow_points = this.read_u32()?;
-                                this.read_string()?
-                            },
-                            glow_points: this.read_list_n(num_glow_points as usize, |this| {
-                                Ok(GlowPoint {
-                                    position: this.read_vec3d()?,
+                            })
+                        })?);
+                        //println!("{:#?}", target);
+                    }
+                    b"TGUN" | b"TMIS" => {
+                        let mut new_turrets = chunk.read_list(|this| {
+                            Ok(Turret {
+                                base_obj: ObjectId(this.read_u32()?),
+                                gun_obj: ObjectId(this.read_u32()?),
+                                normal: this.read_vec3d()?.try_into().unwrap_or_default(),
+                                fire_points: this.read_list(|this| this.read_vec3d())?,
+                            })
+                        })?;
+                        // the base and gun objects must precede the turret chunk
+                        let is_missing = |obj_id: &ObjectId| !matches!(sub_objects.get(obj_id.0 as usize), Some(Some(_)));
+                        for turret in &new_turrets {
+                            if let Some(&obj_id) = [turret.base_obj, turret.gun_obj].iter().find(|obj_id| is_missing(obj_id)) {
+                                if !options.lenient {
+                                    return Err(ParseError::TurretMissingSubObject { id: *id, offset: chunk_offset, obj_id });
+                                }
+                                ParseDiagnostic::record(
+                                    &mut diagnostics,
+                                    Some((*id, chunk_offset)),
+                                    format!(
+                                        "turret on subobject {} refers to missing subobject {}; the turret was discarded",
+                                        turret.base_obj.0, obj_id.0
+                                    ),
+                                );
+                            }
+                        }
+                        new_turrets.retain(|turret| !is_missing(&turret.base_obj) && !is_missing(&turret.gun_obj));
+                        turrets.extend(new_turrets);
+                        //println!("{:#?}", turrets);
+                    }
+                    b"FUEL" => {
+                        ensure_unique!(thruster_banks);
+                        thruster_banks = Some(chunk.read_list(|this| {
+                            let num_glows = this.read_u32()?;
+                            Ok(ThrusterBank {
+                                properties: if this.version >= Version::V21_17 {
+                                    this.read_string()?
+                                } else {
+                                    String::new()
+                                },
+                                glows: this.read_list_n(num_glows as usize, |this| {
+                                    Ok(ThrusterGlow {
+                                        position: this.read_vec3d()?,
+                                        normal: this.read_vec3d()?.try_into().unwrap_or_default(),
+                                        // TODO document this at https://wiki.hard-light.net/index.php/POF_data_structure
+                                        radius: if this.version > Version::V20_04 { this.read_f32()? } else { 1.0 },
+                                    })
+                                })?,
+                            })
+                        })?);
+                        //println!("{:#?}", thruster_banks);
+                    }
+                    b"GLOW" => {
+                        ensure_unique!(glow_banks);
+                        glow_banks = Some(chunk.read_list(|this| {
+                            let num_glow_points;
+                            Ok(GlowPointBank {
+                                disp_time: this.read_i32()?,
+                                on_time: this.read_u32()?,
+                                off_time: this.read_u32()?,
+                                obj_parent: ObjectId(this.read_u32()?),
+                                lod: this.read_u32()?,
+                                glow_type: this.read_u32()?,
+                                properties: {
+                                    num_glow_points = this.read_u32()?;
+                                    this.read_string()?
+                                },
+                                glow_points: this.read_list_n(num_glow_points as usize, |this| {
+                                    Ok(GlowPoint {
+                                        position: this.read_vec3d()?,
+                                        normal: this.read_vec3d()?,
+                                        radius: this.read_f32()?,
+                                    })
+                                })?,
+                            })
+                        })?);
+                        //println!("{:#?}", glow_banks);
+                    }
+                    b"ACEN" => {
+                        ensure_unique!(visual_center);
+                        visual_center = Some(chunk.read_vec3d()?);
+                    }
+                    b"DOCK" => {
+                        ensure_unique!(dock_points);
+                        dock_points = Some(chunk.read_list(|this| {
+                            let properties = this.read_string()?;
+                            let used_paths = this.read_list(|this| this.read_u32())?; // spec allows for a list of paths but only the first will be used so dont bother
+                            let path = used_paths.first().map(|&x| PathId(x));
+                            // same thing here, only first 2 are used
+                            let mut dockpoints =
+                                this.read_list(|this| Ok(DockingPoint { position: this.read_vec3d()?, normal: this.read_vec3d()? }))?;
+                            let mut iter = dockpoints.drain(..dockpoints.len().min(2));
+                            let (p1, p2) = (iter.next().unwrap_or_default(), iter.next().unwrap_or_default());
+                            let position = (p1.position + p2.position) / 2.0;
+                            let fvec: NormalVec3 = p1.normal.try_into().unwrap_or_default();
+                            let uvec = Dock::orthonormalize(&(p2.position - p1.position).into(), &fvec.0.into());
+
+                            Ok(Dock { properties, path, position, fvec, uvec: uvec.into() })
+                        })?);
+                        //println!("{:#?}", dock_points);
+                    }
+                    b"INSG" => {
+                        ensure_unique!(insignias);
+                        insignias = Some(chunk.read_list(|this| {
+                            let num_faces;
+                            Ok(Insignia {
+                                detail_level: this.read_u32()?,
+                                vertices: {
+                                    num_faces = this.read_u32()?;
+                                    this.read_list(|this| this.read_vec3d())?
+                                },
+                                offset: this.read_vec3d()?,
+                                faces: this.read_list_n(num_faces as usize, |this| {
+                                    let [x, y, z] = *this.read_array(|this| {
+                                        Ok(PolyVertex {
+                                            vertex_id: VertexId(this.read_u32()?),
+                                            normal_id: (),
+                                            uv: (this.read_f32()?, this.read_f32()?),
+                                        })
+                                    })?;
+                                    Ok((x, y, z))
+                                })?,
+                            })
+                        })?);
+                        //println!("{:#?}", insignias);
+                    }
+                    b"SHLD" => {
+                        ensure_unique!(shield_data);
+                        shield_data = Some((
+                            chunk.read_list(|this| this.read_vec3d())?,
+                            chunk.read_list(|this| {
+                                Ok(ShieldPolygon {
                                     normal: this.read_vec3d()?,
-                                    radius: this.read_f32()?,
+                                    verts: (VertexId(this.read_u32()?), VertexId(this.read_u32()?), VertexId(this.read_u32()?)),
+                                    neighbors: (PolygonId(this.read_u32()?), PolygonId(this.read_u32()?), PolygonId(this.read_u32()?)),
                                 })
                             })?,
-                        })
-                    })?);
-                    //println!("{:#?}", glow_banks);
-                }
-                b"ACEN" => {
-                    assert!(visual_center.is_none());
-                    visual_center = Some(self.read_vec3d()?);
-                }
-                b"DOCK" => {
-                    assert!(dock_points.is_none());
-                    dock_points = Some(self.read_list(|this| {
-                        let properties = this.read_string()?;
-                        let used_paths = this.read_list(|this| this.read_u32())?; // spec allows for a list of paths but only the first will be used so dont bother
-                        let path = used_paths.first().map(|&x| PathId(x));
-                        // same thing here, only first 2 are used
-                        let mut dockpoints = this.read_list(|this| Ok(DockingPoint { position: this.read_vec3d()?, normal: this.read_vec3d()? }))?;
-                        let mut iter = dockpoints.drain(..2);
-                        let (p1, p2) = (iter.next().unwrap_or_default(), iter.next().unwrap_or_default());
-                        let position = (p1.position + p2.position) / 2.0;
-                        let fvec: NormalVec3 = p1.normal.try_into().unwrap_or_default();
-                        let uvec = Dock::orthonormalize(&(p2.position - p1.position).into(), &fvec.0.into());
-
-                        Ok(Dock { properties, path, position, fvec, uvec: uvec.into() })
-                    })?);
-                    //println!("{:#?}", dock_points);
-                }
-                b"INSG" => {
-                    assert!(insignias.is_none());
-                    insignias = Some(self.read_list(|this| {
-                        let num_faces;
-                        Ok(Insignia {
-                            detail_level: this.read_u32()?,
-                            vertices: {
-                                num_faces = this.read_u32()?;
-                                this.read_list(|this| this.read_vec3d())?
-                            },
-                            offset: this.read_vec3d()?,
-                            faces: this.read_list_n(num_faces as usize, |this| {
-                                let [x, y, z] = *this.read_array(|this| {
-                                    Ok(PolyVertex {
-                                        vertex_id: VertexId(this.read_u32()?),
-                                        normal_id: (),
-                                        uv: (this.read_f32()?, this.read_f32()?),
-                                    })
-                                })?;
-                                Ok((x, y, z))
-                            })?,
-                        })
-                    })?);
-                    //println!("{:#?}", insignias);
-                }
-                b"SHLD" => {
-                    assert!(shield_data.is_none());
-                    shield_data = Some((
-                        self.read_list(|this| this.read_vec3d())?,
-                        self.read_list(|this| {
-                            Ok(ShieldPolygon {
-                                normal: this.read_vec3d()?,
-                                verts: (VertexId(this.read_u32()?), VertexId(this.read_u32()?), VertexId(this.read_u32()?)),
-                                neighbors: (PolygonId(this.read_u32()?), PolygonId(this.read_u32()?), PolygonId(this.read_u32()?)),
-                            })
-                        })?,
-                    ))
-                }
-                b"SLDC" | b"SLC2" => {
-                    assert!(shield_tree_chunk.is_none());
-                    assert_eq!(self.version >= Version::V22_00, id == b"SLC2");
-                    // deal with this later, once we're sure to also have the shield data
-                    shield_tree_chunk = Some(self.read_byte_buffer()?);
-                }
-                b"PINF" => {
-                    assert!(comments.is_none());
-                    // gotta inline some stuff because the length of this string is the length of the chunk
-                    let mut buffer = vec![0; len as usize];
-                    self.file.read_exact(&mut buffer)?;
-
-                    let end = buffer.iter().position(|&char| char == 0).unwrap_or(buffer.len());
-                    comments = Some(String::from_utf8_lossy(buffer[..end].into()).to_string());
-                    // println!("{:#?}", comments);
-                }
-                _ => {
-                    eprintln!("I don't know how to handle id {:x?}", id);
-                    self.file.seek(SeekFrom::Current(len as i64))?;
+                        ))
+                    }
+                    b"SLDC" | b"SLC2" => {
+                        ensure_unique!(shield_tree_chunk);
+                        if (self.version >= Version::V22_00) != (id == b"SLC2") {
+                            invalid!("{} chunk is not valid in version {}", chunk_id_str(id), self.version);
+                        }
+                        // deal with this later, once we're sure to also have the shield data
+                        shield_tree_chunk = Some((*id, chunk.read_byte_buffer()?));
+                        shield_tree_offset = chunk_offset;
+                    }
+                    b"PINF" => {
+                        ensure_unique!(comments);
+                        // gotta inline some stuff because the length of this string is the length of the chunk
+                        let buffer = &data;
+
+                        let end = buffer.iter().position(|&char| char == 0).unwrap_or(buffer.len());
+                        comments = Some(String::from_utf8_lossy(buffer[..end].into()).to_string());
+                        // println!("{:#?}", comments);
+                    }
+                    _ => {
+                        warn!("Unknown chunk {} will be preserved as-is", chunk_id_str(id));
+                        unknown_chunks.push(UnknownChunk { id: *id, data: data.clone() });
+                    }
                 }
+                Ok(())
+            })();
+
+            let err = match chunk_result {
+                Ok(()) => continue,
+                Err(ParseError::Io(err)) if err.kind() == ErrorKind::UnexpectedEof => ParseError::InvalidChunk {
+                    id: *id,
+                    offset: chunk_offset,
+                    reason: format!("contents run past the chunk length of {} bytes", data.len()),
+                },
+                Err(err) => err,
+            };
+            if !options.lenient {
+                return Err(err);
             }
+
+            // the chunk has already been read in its entirety, so just move on and hope the next one is in better shape
+            ParseDiagnostic::record(&mut diagnostics, Some((*id, chunk_offset)), format!("{}; the chunk was skipped", err.reason()));
+        }
+
+        // now parse the BSP data of every subobject, these are the bulk of the file so this is where the time goes
+        let version = self.version;
+        let bsp_results = map_maybe_parallel(bsp_data_buffers, |(obj_id, id, chunk_offset, buf)| {
+            let result = parse_bsp_data(&buf, version);
+            (obj_id, id, chunk_offset, buf, result)
+        });
+        for (obj_id, id, chunk_offset, buf, result) in bsp_results {
+            let subobj = sub_objects[obj_id.0 as usize].as_mut().unwrap();
+            subobj.bsp_data = match result {
+                Ok(bsp_data) => bsp_data,
+                Err(err) if options.lenient => {
+                    // the tree is a lost cause, but the vertices may still be intact
+                    let (verts, norms) = parse_bsp_points(&buf).map(|(verts, norms, _)| (verts, norms)).unwrap_or_default();
+                    ParseDiagnostic::record(
+                        &mut diagnostics,
+                        Some((id, chunk_offset)),
+                        format!(
+                            "BSP data of subobject {} is unreadable ({}); its polygons were dropped, keeping {} vertices and {} normals",
+                            subobj.name,
+                            err,
+                            verts.len(),
+                            norms.len()
+                        ),
+                    );
+                    BspData { verts, norms, collision_tree: BspNode::Empty }
+                }
+                Err(err) => return Err(ParseError::MalformedBsp { id, offset: chunk_offset, reason: err.to_string() }),
+            };
         }
 
         // finally handle the shield tree, if applicable
@@ -468,33 +942,111 @@ impl<R: Read + Seek> Parser<R> {
                 verts,
                 polygons: poly_list,
                 collision_tree: match shield_tree_chunk {
-                    Some(chunk) => Some(*parse_shield_node(&chunk, self.version)?),
+                    Some((id, chunk)) => match parse_shield_node(&chunk, self.version) {
+                        Ok(tree) => Some(*tree),
+                        Err(err) if options.lenient => {
+                            ParseDiagnostic::record(
+                                &mut diagnostics,
+                                Some((id, shield_tree_offset)),
+                                format!("shield collision tree is unreadable ({}); it was dropped and will be recalculated", err),
+                            );
+                            None
+                        }
+                        Err(err) => return Err(ParseError::MalformedBsp { id, offset: shield_tree_offset, reason: err.to_string() }),
+                    },
                     None => None,
                 },
             }),
-            (None, Some(_)) => unreachable!(),
+            (None, Some((id, _))) if options.lenient => {
+                ParseDiagnostic::record(
+                    &mut diagnostics,
+                    Some((id, shield_tree_offset)),
+                    "shield collision tree has no shield mesh to go with it; it was discarded".to_string(),
+                );
+                None
+            }
+            (None, Some(_)) => return Err(ParseError::MissingChunk(*b"SHLD")),
             _ => None,
         };
 
-        // now that all the subobjects shouldve have been slotted in, assert that they all exist
-        let mut sub_objects = ObjVec(sub_objects.into_iter().map(|subobj_opt| subobj_opt.unwrap()).collect());
+        // now that all the subobjects shouldve have been slotted in, make sure that they all exist
+        let mut sub_objects = ObjVec(
+            sub_objects
+                .into_iter()
+                .enumerate()
+                .map(|(i, subobj_opt)| match subobj_opt {
+                    Some(subobj) => Ok(subobj),
+                    None if options.lenient => {
+                        ParseDiagnostic::record(
+                            &mut diagnostics,
+                            None,
+                            format!("subobject {} is missing; an empty placeholder was put in its place", i),
+                        );
+                        Ok(SubObject {
+                            obj_id: ObjectId(i as u32),
+                            name: format!("missing-subobject-{}", i),
+                            ..Default::default()
+                        })
+                    }
+                    None => Err(ParseError::MissingSubObject(ObjectId(i as u32))),
+                })
+                .collect::<Result<_, _>>()?,
+        );
+
+        // in strict mode, this was already checked when parsing the subobjects
+        let num_subobjects = sub_objects.len() as u32;
+        for subobj in sub_objects.iter_mut() {
+            if subobj.parent.map_or(false, |id| id.0 >= num_subobjects) {
+                ParseDiagnostic::record(
+                    &mut diagnostics,
+                    None,
+                    format!("parent {} of subobject {} does not exist; it was made a top-level subobject", subobj.parent.unwrap().0, subobj.name),
+                );
+                subobj.parent = None;
+            }
+        }
 
         debris_objs.retain(|id| {
             if id.0 < sub_objects.len() as u32 {
                 sub_objects[*id].is_debris_model = true;
                 true
             } else {
-                warn!("Invalid debris object {} discarded", id.0);
+                ParseDiagnostic::record(&mut diagnostics, None, format!("Invalid debris object {} discarded", id.0));
                 false
             }
         });
 
-        let mut header = header.expect("No header chunk found???");
+        let header_id = if self.version >= Version::V21_16 { *b"HDR2" } else { *b"OHDR" };
+        let mut header = match header {
+            Some(header) => header,
+            None if options.lenient => {
+                ParseDiagnostic::record(
+                    &mut diagnostics,
+                    None,
+                    format!("{} chunk is missing; a default header was created", chunk_id_str(&header_id)),
+                );
+                ObjHeader::default()
+            }
+            None => return Err(ParseError::MissingChunk(header_id)),
+        };
+        // the header's count is only trusted this late, once it can be compared against the subobjects that were actually there
+        if header.num_subobjects > num_subobjects {
+            if !options.lenient {
+                return Err(ParseError::MissingSubObject(ObjectId(num_subobjects)));
+            }
+            ParseDiagnostic::record(
+                &mut diagnostics,
+                None,
+                format!("the header declares {} subobjects, but only {} were found; the count was corrected", header.num_subobjects, num_subobjects),
+            );
+        }
+        // only differs in lenient mode, where subobjects can be added or the header lost
+        header.num_subobjects = num_subobjects;
         header.detail_levels.retain(|id| {
             if id.0 < sub_objects.len() as u32 {
                 true
             } else {
-                warn!("Invalid detail level object {} discarded", id.0);
+                ParseDiagnostic::record(&mut diagnostics, None, format!("Invalid detail level object {} discarded", id.0));
                 false
             }
         });
@@ -504,7 +1056,7 @@ impl<R: Read + Seek> Parser<R> {
             for dock in points.iter_mut() {
                 if dock.path.map_or(false, |id| id.0 >= paths.as_ref().map_or(0, |paths| paths.len()) as u32) {
                     dock.path = None;
-                    warn!("Invalid dock path on {:?} reset", dock.get_name());
+                    ParseDiagnostic::record(&mut diagnostics, None, format!("Invalid dock path on {:?} reset", dock.get_name()));
                 }
             }
         }
@@ -514,7 +1066,7 @@ impl<R: Read + Seek> Parser<R> {
             for (i, eye) in points.iter_mut().enumerate() {
                 if eye.attached_subobj.map_or(false, |id| id.0 >= sub_objects.len() as u32) {
                     eye.attached_subobj = None;
-                    warn!("Invalid eye point {} reset", i);
+                    ParseDiagnostic::record(&mut diagnostics, None, format!("Invalid eye point {} reset", i));
                 }
             }
         }
@@ -540,6 +1092,7 @@ impl<R: Read + Seek> Parser<R> {
             glow_banks: glow_banks.unwrap_or_default(),
             visual_center: visual_center.unwrap_or_default(),
             shield_data,
+            unknown_chunks,
             path_to_file: path.canonicalize().unwrap_or(path),
             untextured_idx,
             warnings: Default::default(),
@@ -551,7 +1104,53 @@ impl<R: Read + Seek> Parser<R> {
         model.recheck_errors(Set::All);
         model.recalc_semantic_name_links();
 
-        Ok(model)
+        Ok((model, diagnostics))
+    }
+
+    /// Walks the rest of the file chunk by chunk, see [`inspect`].
+    ///
+    /// Damaged chunks are noted in the layout rather than failing, since the whole point is to see what is really in the file.
+    pub fn inspect(&mut self) -> Result<PofLayout, ParseError> {
+        let mut chunks = vec![];
+        loop {
+            let offset = self.file.position;
+            let id = match self.read_bytes() {
+                Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+                id_result => id_result?,
+            };
+            let mut chunk = ChunkLayout { id, offset, len: 0, subobject: None, problem: None };
+
+            let data = match self.read_i32().and_then(|len| {
+                chunk.len = len.try_into().map_err(|_| invalid_data(format!("negative chunk length {}", len)))?;
+                self.read_exact_vec(chunk.len as usize)
+            }) {
+                Ok(data) => data,
+                Err(err) if err.kind() == ErrorKind::UnexpectedEof || err.kind() == ErrorKind::InvalidData => {
+                    chunk.problem = Some(if err.kind() == ErrorKind::UnexpectedEof {
+                        "chunk is truncated".to_string()
+                    } else {
+                        err.to_string()
+                    });
+                    chunks.push(chunk);
+                    break;
+                }
+                Err(err) => return Err(err.into()),
+            };
+
+            if &id == b"OBJ2" || &id == b"SOBJ" {
+                match inspect_subobject(&data, self.version) {
+                    Ok((mut subobject, problem)) => {
+                        subobject.bsp_offset += offset + 8;
+                        chunk.subobject = Some(subobject);
+                        chunk.problem = problem;
+                    }
+                    Err(err) => chunk.problem = Some(err.to_string()),
+                }
+            }
+            chunks.push(chunk);
+        }
+
+        Ok(PofLayout { version: self.version, chunks })
     }
 
     fn read_list<T>(&mut self, f: impl FnMut(&mut Self) -> io::Result<T>) -> io::Result<Vec<T>> {
@@ -582,11 +1181,19 @@ impl<R: Read + Seek> Parser<R> {
     }
 
     fn read_byte_buffer(&mut self) -> io::Result<Box<[u8]>> {
-        let mut buffer = vec![0; self.read_u32()? as usize];
-        //println!("buffer size is {}", buffer.len());
-        self.file.read_exact(&mut buffer)?;
+        let len = self.read_u32()? as usize;
+        //println!("buffer size is {}", len);
+        Ok(self.read_exact_vec(len)?.into())
+    }
 
-        Ok(buffer.into())
+    // like read_exact, but doesn't trust `len` enough to allocate it all up front, since it might be garbage from a damaged file
+    fn read_exact_vec(&mut self, len: usize) -> io::Result<Vec<u8>> {
+        let mut buffer = vec![];
+        (&mut self.file).take(len as u64).read_to_end(&mut buffer)?;
+        if buffer.len() < len {
+            return Err(ErrorKind::UnexpectedEof.into());
+        }
+        Ok(buffer)
     }
 
     fn read_bytes<const N: usize>(&mut self) -> io::Result<[u8; N]> {
@@ -610,6 +1217,12 @@ impl<R: Read + Seek> Parser<R> {
     }
 }
 
+fn first_difference(a: &[u8], b: &[u8]) -> Option<u64> {
+    let common = a.iter().zip(b).position(|(a, b)| a != b);
+    let len_mismatch = (a.len() != b.len()).then(|| a.len().min(b.len()));
+    common.or(len_mismatch).map(|offset| offset as u64)
+}
+
 fn read_i32(file: &mut impl Read) -> io::Result<i32> {
     Ok(i32::from_le_bytes(read_bytes(file)?))
 }
@@ -667,10 +1280,25 @@ fn parse_chunk_header(buf: &[u8], chunk_type_is_u8: bool) -> io::Result<(u32, &[
         }
     );*/
     let chunk_size = pointer.read_u32::<LE>()? as usize;
-    Ok((chunk_type, pointer, &buf[chunk_size..]))
+    let header_size = if chunk_type_is_u8 { 5 } else { 8 };
+    // ENDOFBRANCH is conventionally written with a size of 0, and nothing follows it anyway
+    let is_end_of_branch = !chunk_type_is_u8 && chunk_type == BspData::ENDOFBRANCH;
+    if chunk_size < header_size && !is_end_of_branch {
+        return Err(invalid_data(format!("chunk type {} has invalid size {}", chunk_type, chunk_size)));
+    }
+    let next_chunk = buf
+        .get(chunk_size..)
+        .ok_or_else(|| invalid_data(format!("chunk type {} of size {} overruns its buffer", chunk_type, chunk_size)))?;
+    Ok((chunk_type, pointer, next_chunk))
+}
+
+// get the rest of the buffer starting at a chunk-relative offset, failing if its out of range
+fn buf_at_offset(buf: &[u8], offset: u32) -> io::Result<&[u8]> {
+    buf.get(offset as usize..)
+        .ok_or_else(|| invalid_data(format!("offset {} is out of range", offset)))
 }
 
-fn parse_bsp_data(mut buf: &[u8], version: Version) -> io::Result<BspData> {
+fn parse_bsp_data(buf: &[u8], version: Version) -> io::Result<BspData> {
     fn parse_bsp_node(mut buf: &[u8], verts: &[Vec3d], version: Version) -> io::Result<Box<BspNode>> {
         // parse the first header
         let (chunk_type, mut chunk, next_chunk) = parse_chunk_header(buf, false)?;
@@ -689,7 +1317,7 @@ fn parse_bsp_data(mut buf: &[u8], version: Version) -> io::Result<BspData> {
                     if offset == 0 {
                         Box::new(BspNode::Empty)
                     } else {
-                        parse_bsp_node(&buf[offset as usize..], verts, version)?
+                        parse_bsp_node(buf_at_offset(buf, offset)?, verts, version)?
                     }
                 },
                 back: {
@@ -697,7 +1325,7 @@ fn parse_bsp_data(mut buf: &[u8], version: Version) -> io::Result<BspData> {
                     if offset == 0 {
                         Box::new(BspNode::Empty)
                     } else {
-                        parse_bsp_node(&buf[offset as usize..], verts, version)?
+                        parse_bsp_node(buf_at_offset(buf, offset)?, verts, version)?
                     }
                 },
                 bbox: {
@@ -758,7 +1386,7 @@ fn parse_bsp_data(mut buf: &[u8], version: Version) -> io::Result<BspData> {
                             break;
                         }
                         _ => {
-                            unreachable!("unknown chunk type! {}", chunk_type);
+                            return Err(invalid_data(format!("unexpected chunk type {} in polygon list", chunk_type)));
                         }
                     });
 
@@ -792,22 +1420,39 @@ fn parse_bsp_data(mut buf: &[u8], version: Version) -> io::Result<BspData> {
             }
             BspData::ENDOFBRANCH => BspNode::Empty,
             _ => {
-                unreachable!();
+                return Err(invalid_data(format!("unexpected chunk type {} in BSP tree", chunk_type)));
             }
         }))
     }
 
     //println!("started parsing a bsp tree");
 
+    let (verts, norms, next_chunk) = parse_bsp_points(buf)?;
+
+    let mut bsp_tree = *parse_bsp_node(next_chunk, &verts, version)?;
+
+    if version < Version::V20_00 {
+        bsp_tree.recalculate_bboxes(&verts);
+    }
+
+    Ok(BspData { collision_tree: bsp_tree, norms, verts })
+}
+
+/// Reads the DEFFPOINTS chunk at the start of the BSP data, returning the vertices, normals and the rest of the buffer
+fn parse_bsp_points(mut buf: &[u8]) -> io::Result<(Vec<Vec3d>, Vec<Vec3d>, &[u8])> {
     let (chunk_type, mut chunk, next_chunk) = parse_chunk_header(buf, false)?;
-    assert!(chunk_type == BspData::DEFFPOINTS);
+    if chunk_type != BspData::DEFFPOINTS {
+        return Err(invalid_data(format!("expected DEFFPOINTS, found chunk type {}", chunk_type)));
+    }
 
     let num_verts = chunk.read_u32::<LE>()?;
     let num_norms = chunk.read_u32::<LE>()?;
     let offset = chunk.read_u32::<LE>()?;
-    let norm_counts = &chunk[0..num_verts as usize];
+    let norm_counts = chunk
+        .get(0..num_verts as usize)
+        .ok_or_else(|| invalid_data(format!("DEFFPOINTS is too short for {} vertices", num_verts)))?;
 
-    buf = &buf[offset as usize..];
+    buf = buf_at_offset(buf, offset)?;
 
     let mut verts = vec![];
     let mut norms = vec![];
@@ -818,15 +1463,107 @@ fn parse_bsp_data(mut buf: &[u8], version: Version) -> io::Result<BspData> {
         }
     }
 
-    assert!(num_norms as usize == norms.len());
+    if num_norms as usize != norms.len() {
+        return Err(invalid_data(format!("DEFFPOINTS declares {} normals but contains {}", num_norms, norms.len())));
+    }
 
-    let mut bsp_tree = *parse_bsp_node(next_chunk, &verts, version)?;
+    Ok((verts, norms, next_chunk))
+}
 
-    if version < Version::V20_00 {
-        bsp_tree.recalculate_bboxes(&verts);
+/// Reads just enough of a subobject chunk to find its BSP data, and walks that.
+/// The returned BSP offset is relative to the start of the chunk contents.
+fn inspect_subobject(chunk: &[u8], version: Version) -> io::Result<(SubObjectLayout, Option<String>)> {
+    fn read_string(buf: &mut &[u8]) -> io::Result<String> {
+        let len = buf.read_u32::<LE>()? as usize;
+        let bytes = buf.get(..len).ok_or(ErrorKind::UnexpectedEof)?;
+        *buf = &buf[len..];
+        let end = bytes.iter().position(|&char| char == 0).unwrap_or(bytes.len());
+        Ok(String::from_utf8_lossy(&bytes[..end]).to_string())
     }
 
-    Ok(BspData { collision_tree: bsp_tree, norms, verts })
+    let mut buf = chunk;
+    let obj_id = buf.read_u32::<LE>()?;
+    // radius, parent and offset, in whichever order
+    buf = buf.get(20..).ok_or(ErrorKind::UnexpectedEof)?;
+    let _geo_center = read_vec3d(&mut buf)?;
+    let _bbox = read_bbox(&mut buf)?;
+    let name = read_string(&mut buf)?;
+    let _properties = read_string(&mut buf)?;
+    // rotation type and axis, and translation type and axis if present
+    let num_movement_fields = if version >= Version::V23_01 { 4 } else { 2 };
+    buf = buf.get(4 * num_movement_fields..).ok_or(ErrorKind::UnexpectedEof)?;
+    let _chunked = buf.read_i32::<LE>()?;
+    let bsp_len = buf.read_u32::<LE>()?;
+    let bsp_offset = (chunk.len() - buf.len()) as u64;
+
+    let (bsp, problem) = match buf.get(..bsp_len as usize) {
+        Some(bsp) => (bsp, None),
+        None => (buf, Some(format!("BSP data claims {} bytes but only {} remain in the chunk", bsp_len, buf.len()))),
+    };
+
+    let mut blocks = vec![];
+    let problem = problem.or_else(|| {
+        walk_bsp_blocks(bsp, &mut blocks)
+            .err()
+            .map(|err| format!("BSP data is malformed: {}", err))
+    });
+    blocks.sort_by_key(|block| block.offset);
+
+    Ok((SubObjectLayout { obj_id, name, bsp_offset, bsp_len, blocks }, problem))
+}
+
+/// Follows the BSP data the same way [`parse_bsp_data`] does, recording every block it comes across
+fn walk_bsp_blocks(bsp: &[u8], blocks: &mut Vec<BspBlockLayout>) -> io::Result<()> {
+    // returns the block type, or None if this block was already visited (only possible in a damaged file)
+    fn record(bsp: &[u8], block: &[u8], blocks: &mut Vec<BspBlockLayout>, seen: &mut HashSet<u32>) -> io::Result<Option<u32>> {
+        let offset = (bsp.len() - block.len()) as u32;
+        let mut header = block;
+        let kind = header.read_u32::<LE>()?;
+        let size = header.read_u32::<LE>()?;
+        if !seen.insert(offset) {
+            return Ok(None);
+        }
+        blocks.push(BspBlockLayout { kind, offset, size });
+        Ok(Some(kind))
+    }
+
+    fn walk_node(bsp: &[u8], node: &[u8], blocks: &mut Vec<BspBlockLayout>, seen: &mut HashSet<u32>) -> io::Result<()> {
+        let Some(kind) = record(bsp, node, blocks, seen)? else { return Ok(()) };
+        let (_, mut chunk, mut next_chunk) = parse_chunk_header(node, false)?;
+        match kind {
+            BspData::SORTNORM | BspData::SORTNORM2 => {
+                if kind == BspData::SORTNORM {
+                    // skip the unused normal, point and reserved fields
+                    chunk = chunk.get(28..).ok_or(ErrorKind::UnexpectedEof)?;
+                }
+                for _ in 0..2 {
+                    let offset = chunk.read_u32::<LE>()?;
+                    if offset != 0 {
+                        walk_node(bsp, buf_at_offset(node, offset)?, blocks, seen)?;
+                    }
+                }
+            }
+            BspData::BOUNDBOX => {
+                // the polygons follow the bounding box, up to the next ENDOFBRANCH
+                while let Some(kind) = record(bsp, next_chunk, blocks, seen)? {
+                    if kind == BspData::ENDOFBRANCH {
+                        break;
+                    }
+                    next_chunk = parse_chunk_header(next_chunk, false)?.2;
+                }
+            }
+            BspData::TMAPPOLY2 | BspData::ENDOFBRANCH => {}
+            _ => return Err(invalid_data(format!("unexpected chunk type {} in BSP tree", kind))),
+        }
+        Ok(())
+    }
+
+    let mut seen = HashSet::new();
+    let kind = record(bsp, bsp, blocks, &mut seen)?;
+    if kind != Some(BspData::DEFFPOINTS) {
+        return Err(invalid_data(format!("expected DEFFPOINTS, found chunk type {}", kind.unwrap_or_default())));
+    }
+    walk_node(bsp, parse_chunk_header(bsp, false)?.2, blocks, &mut seen)
 }
 
 fn parse_shield_node(buf: &[u8], version: Version) -> io::Result<Box<ShieldNode>> {
@@ -836,20 +1573,24 @@ fn parse_shield_node(buf: &[u8], version: Version) -> io::Result<Box<ShieldNode>
             bbox: read_bbox(&mut chunk)?,
             front: {
                 let offset = chunk.read_u32::<LE>()?;
-                assert!(offset != 0);
-                parse_shield_node(&buf[offset as usize..], version)?
+                if offset == 0 {
+                    return Err(invalid_data("shield split node has no front branch"));
+                }
+                parse_shield_node(buf_at_offset(buf, offset)?, version)?
             },
             back: {
                 let offset = chunk.read_u32::<LE>()?;
-                assert!(offset != 0);
-                parse_shield_node(&buf[offset as usize..], version)?
+                if offset == 0 {
+                    return Err(invalid_data("shield split node has no back branch"));
+                }
+                parse_shield_node(buf_at_offset(buf, offset)?, version)?
             },
         },
         ShieldNode::LEAF => ShieldNode::Leaf {
             bbox: read_bbox(&mut chunk)?,
             poly_list: read_list_n(chunk.read_u32::<LE>()? as usize, &mut chunk, |chunk| Ok(PolygonId(chunk.read_u32::<LE>()?)))?,
         },
-        _ => unreachable!(),
+        _ => return Err(invalid_data(format!("unexpected chunk type {} in shield tree", chunk_type))),
     }))
 }
 
@@ -857,6 +1598,106 @@ fn parse_shield_node(buf: &[u8], version: Version) -> io::Result<Box<ShieldNode>
 // DAE / glTF parsing
 // =================================================================
 
+/// The ways in which importing a DAE or glTF file can fail.
+pub enum ImportError {
+    /// The file (or a buffer it refers to) could not be read
+    Io(io::Error),
+    /// The file is not valid COLLADA
+    Dae(dae::Error),
+    /// The file is not valid glTF
+    Gltf(gltf::Error),
+    /// The file was read, but refers to data which doesn't exist or doesn't make sense
+    Malformed(String),
+}
+impl Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::Io(err) => write!(f, "{}", err),
+            ImportError::Dae(err) => write!(f, "invalid COLLADA file: {:?}", err),
+            ImportError::Gltf(err) => write!(f, "invalid glTF file: {}", err),
+            ImportError::Malformed(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+impl Debug for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+impl std::error::Error for ImportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ImportError::Io(err) => Some(err),
+            ImportError::Gltf(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+impl From<io::Error> for ImportError {
+    fn from(err: io::Error) -> Self {
+        ImportError::Io(err)
+    }
+}
+
+/// Everything in an imported file which didn't make it into the model as-is, and why
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub diagnostics: Vec<ImportDiagnostic>,
+}
+impl ImportReport {
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    fn record(&mut self, node: Option<&str>, message: impl Into<String>) {
+        let diagnostic = ImportDiagnostic { node: node.map(str::to_string), message: message.into() };
+        warn!("{}", diagnostic);
+        self.diagnostics.push(diagnostic);
+    }
+}
+
+/// A node that was skipped or only partly imported
+#[derive(Debug)]
+pub struct ImportDiagnostic {
+    /// The name of the node, if it has one
+    pub node: Option<String>,
+    pub message: String,
+}
+impl Display for ImportDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.node {
+            Some(node) => write!(f, "{}: {}", node, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+// vertices, normals and polygons of a node
+type Geometry = (Vec<Vec3d>, Vec<Vec3d>, Vec<(TextureId, Vec<PolyVertex>)>);
+
+// parses the value of a metadata node like `#mov-type:1`, noting it in the report if there isn't a sensible one
+fn metadata_value<T: std::str::FromStr>(name: &str, report: &mut ImportReport) -> Option<T> {
+    let Some(idx) = name.find(':') else {
+        report.record(Some(name), "metadata node has no value after a ':', so it was ignored");
+        return None;
+    };
+    let value = name[(idx + 1)..].parse().ok();
+    if value.is_none() {
+        report.record(Some(name), format!("could not parse the value '{}', so it was ignored", &name[(idx + 1)..]));
+    }
+    value
+}
+
+// same as `metadata_value`, for values which are stored as an integer but must be one of a few options
+fn metadata_enum<T: TryFrom<i32>>(name: &str, report: &mut ImportReport) -> Option<T> {
+    let value = metadata_value::<i32>(name, report)?;
+    let value = value.try_into().ok();
+    if value.is_none() {
+        report.record(Some(name), "value is out of range, so it was ignored");
+    }
+    value
+}
+
 trait IsNode<'a>: Clone {
     type Children: Iterator<Item = Self>;
 
@@ -934,11 +1775,24 @@ fn mk_insignia(detail_level: Option<u32>, offset: Vec3d, vertices: Vec<Vec3d>, p
     }
 }
 
+// what builds up over the course of an import, alongside the model itself
+#[derive(Default)]
+struct ImportState {
+    report: ImportReport,
+    // the polygons of each subobject, their BSP trees are only built once every subobject is in so they can all be built at once
+    subobj_polygons: Vec<(ObjectId, Vec<Polygon>)>,
+}
+
+// the BSP tree is left empty, the polygons are handed to the import state to be built into one later
 fn push_subobj(
     sub_objects: &mut Vec<SubObject>, offset: Vec3d, parent: Option<ObjectId>, name: &str, is_debris_model: bool, verts: Vec<Vec3d>,
-    norms: Vec<Vec3d>, polygons: Vec<(TextureId, Vec<PolyVertex>)>,
+    norms: Vec<Vec3d>, polygons: Vec<(TextureId, Vec<PolyVertex>)>, state: &mut ImportState,
 ) -> ObjectId {
     let obj_id = ObjectId(sub_objects.len() as _);
+    let polygons = polygons
+        .into_iter()
+        .map(|(texture, verts)| Polygon { normal: Default::default(), texture, verts });
+    state.subobj_polygons.push((obj_id, polygons.collect()));
 
     let mut new_subobj = SubObject {
         obj_id,
@@ -953,16 +1807,7 @@ fn push_subobj(
         rotation_axis: Default::default(),
         translation_type: Default::default(),
         translation_axis: Default::default(),
-        bsp_data: BspData {
-            norms,
-            collision_tree: BspData::recalculate(
-                &verts,
-                polygons
-                    .into_iter()
-                    .map(|(texture, verts)| Polygon { normal: Default::default(), texture, verts }),
-            ),
-            verts,
-        },
+        bsp_data: BspData { verts, norms, collision_tree: BspNode::Empty },
         children: Default::default(),
         is_debris_model,
         ..Default::default()
@@ -981,11 +1826,19 @@ trait ParseCtx<'a> {
         UpAxis::YUp
     }
 
-    fn parse_geometry(&self, node: &Self::Node, transform: &Mat4x4) -> (Vec<Vec3d>, Vec<Vec3d>, Vec<(TextureId, Vec<PolyVertex>)>);
+    fn parse_geometry(&self, node: &Self::Node, transform: &Mat4x4, report: &mut ImportReport) -> Result<Geometry, ImportError>;
 
-    fn parse_subobject_recursive(&self, model: &mut Model, node: Self::Node, parent: ObjectId, detail_level: Option<u32>, parent_transform: &Mat4x4) {
+    fn parse_subobject_recursive(
+        &self, model: &mut Model, node: Self::Node, parent: ObjectId, detail_level: Option<u32>, parent_transform: &Mat4x4, state: &mut ImportState,
+    ) -> Result<(), ImportError> {
         let name = match node.name() {
-            None => return, // subobjects must have names!
+            None => {
+                // subobjects must have names!
+                state
+                    .report
+                    .record(None, format!("an unnamed child of {} was skipped, subobjects must have names", model.sub_objects[parent].name));
+                return Ok(());
+            }
             Some(name) => name,
         };
         let mut transform = parent_transform * node.transform();
@@ -995,24 +1848,39 @@ trait ParseCtx<'a> {
         let up = self.up();
         let offset = Vec3d::from(center).from_coord(up);
 
-        let (vertices_out, normals_out, polygons_out) = self.parse_geometry(&node, &transform);
+        let (vertices_out, normals_out, polygons_out) = self.parse_geometry(&node, &transform, &mut state.report)?;
 
         // ignore subobjects with no geo
         // metadata (empties with names like #properties) are handled below directly
         // this function must *start* with a proper subobject
         if polygons_out.is_empty() {
-            return;
+            let num_children = node.children().count();
+            if name.starts_with('#') {
+                state.report.record(Some(name), "metadata node isn't recognized here, so it was ignored");
+            } else if num_children == 0 {
+                state
+                    .report
+                    .record(Some(name), "has no geometry and isn't a recognized metadata node, so it was ignored");
+            } else {
+                state.report.record(
+                    Some(name),
+                    format!("has no geometry and isn't a recognized metadata node, so it was ignored along with its {} children", num_children),
+                );
+            }
+            return Ok(());
         }
 
         if name.to_lowercase().contains("insig") {
             model.insignias.push(mk_insignia(detail_level, offset, vertices_out, polygons_out));
         } else {
-            // this should probably be warned about...
             if vertices_out.is_empty() || normals_out.is_empty() {
-                return;
+                state
+                    .report
+                    .record(Some(name), "has no normals, so it was skipped; make sure normals are included in the export");
+                return Ok(());
             }
 
-            let obj_id = push_subobj(&mut model.sub_objects, offset, Some(parent), name, false, vertices_out, normals_out, polygons_out);
+            let obj_id = push_subobj(&mut model.sub_objects, offset, Some(parent), name, false, vertices_out, normals_out, polygons_out, state);
 
             for node in node.children() {
                 // make a pointer to the subobj we just pushed
@@ -1045,42 +1913,37 @@ trait ParseCtx<'a> {
                         node.parse_properties(&mut subobj.properties);
                         continue;
                     } else if name.starts_with('#') && name.contains("mov-type") {
-                        if let Some(idx) = name.find(':') {
-                            if let Ok(val) = name[(idx + 1)..].parse::<i32>() {
-                                subobj.rotation_type = val.try_into().unwrap_or_default();
-                            }
+                        if let Some(val) = metadata_enum(name, &mut state.report) {
+                            subobj.rotation_type = val;
                         }
                         continue;
                     } else if name.starts_with('#') && name.contains("mov-axis") {
-                        if let Some(idx) = name.find(':') {
-                            if let Ok(val) = name[(idx + 1)..].parse::<i32>() {
-                                subobj.rotation_axis = val.try_into().unwrap_or_default();
-                            }
+                        if let Some(val) = metadata_enum(name, &mut state.report) {
+                            subobj.rotation_axis = val;
                         }
                         continue;
                     } else if name.starts_with('#') && name.contains("trans-type") {
-                        if let Some(idx) = name.find(':') {
-                            if let Ok(val) = name[(idx + 1)..].parse::<i32>() {
-                                subobj.translation_type = val.try_into().unwrap_or_default();
-                            }
+                        if let Some(val) = metadata_enum(name, &mut state.report) {
+                            subobj.translation_type = val;
                         }
                         continue;
                     } else if name.starts_with('#') && name.contains("trans-axis") {
-                        if let Some(idx) = name.find(':') {
-                            if let Ok(val) = name[(idx + 1)..].parse::<i32>() {
-                                subobj.translation_axis = val.try_into().unwrap_or_default();
-                            }
+                        if let Some(val) = metadata_enum(name, &mut state.report) {
+                            subobj.translation_axis = val;
                         }
                         continue;
                     }
                 }
 
-                self.parse_subobject_recursive(model, node, obj_id, detail_level, &transform);
+                self.parse_subobject_recursive(model, node, obj_id, detail_level, &transform, state)?;
             }
         }
+        Ok(())
     }
 
-    fn parse_top_level_nodes(&self, model: &mut Model, nodes: impl IntoIterator<Item = Self::Node>) {
+    fn parse_top_level_nodes(
+        &self, model: &mut Model, nodes: impl IntoIterator<Item = Self::Node>, state: &mut ImportState,
+    ) -> Result<(), ImportError> {
         for node in nodes {
             let mut transform = node.transform();
             let zero = Vec3d::ZERO.into();
@@ -1092,10 +1955,13 @@ trait ParseCtx<'a> {
             // println!("{:?}", node.name());
             let name = match node.name() {
                 Some(name) => name,
-                None => continue,
+                None => {
+                    state.report.record(None, "an unnamed top-level node was skipped");
+                    continue;
+                }
             };
 
-            let (vertices_out, normals_out, polygons_out) = self.parse_geometry(&node, &transform);
+            let (vertices_out, normals_out, polygons_out) = self.parse_geometry(&node, &transform, &mut state.report)?;
             if !polygons_out.is_empty() {
                 if name.to_lowercase() == "shield" {
                     let mut polygons = vec![];
@@ -1116,41 +1982,47 @@ trait ParseCtx<'a> {
                         }
                     }
 
-                    // assign shield neighbors
-                    // create a map keyed on each vertex pair, based on winding order, where the value is the polygon id
-                    let mut map: HashMap<(VertexId, VertexId), PolygonId> = HashMap::new();
-                    for (i, poly) in polygons.iter().enumerate() {
-                        map.insert((poly.verts.0, poly.verts.1), PolygonId(i as u32));
-                        map.insert((poly.verts.1, poly.verts.2), PolygonId(i as u32));
-                        map.insert((poly.verts.2, poly.verts.0), PolygonId(i as u32));
-                    }
-
-                    // for each polygon then, by swapping its vertex pairs, you can grab each adjacent polygon
-                    for poly in &mut polygons {
-                        let neighbor1 = map.get(&(poly.verts.1, poly.verts.0)).unwrap_or(&PolygonId(0));
-                        let neighbor2 = map.get(&(poly.verts.2, poly.verts.1)).unwrap_or(&PolygonId(0));
-                        let neighbor3 = map.get(&(poly.verts.0, poly.verts.2)).unwrap_or(&PolygonId(0));
-                        poly.neighbors = (*neighbor1, *neighbor2, *neighbor3);
-                    }
-                    // a map insertion where an entry already exists or a failure to get from the map indicate non-manifoldness, TODO maybe indicate that
-
-                    model.shield_data = Some(ShieldData {
+                    let mut shield = ShieldData {
                         collision_tree: Some(ShieldData::recalculate_tree(&vertices_out, &polygons)),
                         verts: vertices_out,
                         polygons,
-                    });
+                    };
+                    let (open_edges, overused_edges) = shield.recalculate_neighbors();
+                    if open_edges > 0 || overused_edges > 0 {
+                        state.report.record(
+                            Some(name),
+                            format!(
+                                "shield mesh is not manifold ({} open edges, {} edges shared by more than two faces or wound inconsistently); \
+                                it was imported anyway, but collisions near those edges may misbehave",
+                                open_edges, overused_edges
+                            ),
+                        );
+                    }
+
+                    model.shield_data = Some(shield);
                 } else if name.to_lowercase().contains("insig") {
                     model.insignias.push(mk_insignia(None, offset, vertices_out, polygons_out));
                 } else {
                     // must be a subobject
 
-                    // this should probably be warned about...
                     if vertices_out.is_empty() || normals_out.is_empty() {
+                        state
+                            .report
+                            .record(Some(name), "has no normals, so it was skipped; make sure normals are included in the export");
                         continue;
                     }
 
-                    let obj_id =
-                        push_subobj(&mut model.sub_objects, offset, None, name, name.starts_with("debris"), vertices_out, normals_out, polygons_out);
+                    let obj_id = push_subobj(
+                        &mut model.sub_objects,
+                        offset,
+                        None,
+                        name,
+                        name.starts_with("debris"),
+                        vertices_out,
+                        normals_out,
+                        polygons_out,
+                        state,
+                    );
 
                     let mut detail_level: Option<u32> = None;
                     if let Some(idx) = name.to_lowercase().find("detail") {
@@ -1165,7 +2037,7 @@ trait ParseCtx<'a> {
                     }
 
                     for node in node.children() {
-                        self.parse_subobject_recursive(model, node, obj_id, detail_level, &transform);
+                        self.parse_subobject_recursive(model, node, obj_id, detail_level, &transform, state)?;
                     }
                 }
             } else if name == "#thrusters" {
@@ -1227,11 +2099,9 @@ trait ParseCtx<'a> {
                         new_point.normal = norm.try_into().unwrap_or_default();
 
                         for (_, name) in node_children_with_keyword(node, "offset") {
-                            if let Some(idx) = name.find(":") {
-                                if let Ok(val) = &name[(idx + 1)..].parse() {
-                                    new_point.offset = *val;
-                                    break;
-                                }
+                            if let Some(val) = metadata_value(name, &mut state.report) {
+                                new_point.offset = val;
+                                break;
                             }
                         }
 
@@ -1261,10 +2131,8 @@ trait ParseCtx<'a> {
                         if name.contains("properties") {
                             node.parse_properties(&mut new_bay.properties);
                         } else if name.contains("path") {
-                            if let Some(idx) = name.find(":") {
-                                if let Ok(val) = &name[(idx + 1)..].parse() {
-                                    new_bay.path = Some(PathId(*val));
-                                }
+                            if let Some(val) = metadata_value(name, &mut state.report) {
+                                new_bay.path = Some(PathId(val));
                             }
                         }
                     }
@@ -1277,40 +2145,28 @@ trait ParseCtx<'a> {
 
                     for (node, name) in node_children_with_keyword(node, "") {
                         if name.contains("type") {
-                            if let Some(idx) = name.find(":") {
-                                if let Ok(val) = &name[(idx + 1)..].parse() {
-                                    new_bank.glow_type = *val;
-                                }
+                            if let Some(val) = metadata_value(name, &mut state.report) {
+                                new_bank.glow_type = val;
                             }
                         } else if name.contains("lod") {
-                            if let Some(idx) = name.find(":") {
-                                if let Ok(val) = &name[(idx + 1)..].parse() {
-                                    new_bank.lod = *val;
-                                }
+                            if let Some(val) = metadata_value(name, &mut state.report) {
+                                new_bank.lod = val;
                             }
                         } else if name.contains("parent") {
-                            if let Some(idx) = name.find(":") {
-                                if let Ok(val) = &name[(idx + 1)..].parse() {
-                                    new_bank.obj_parent = ObjectId(*val);
-                                }
+                            if let Some(val) = metadata_value(name, &mut state.report) {
+                                new_bank.obj_parent = ObjectId(val);
                             }
                         } else if name.contains("ontime") {
-                            if let Some(idx) = name.find(":") {
-                                if let Ok(val) = &name[(idx + 1)..].parse() {
-                                    new_bank.on_time = *val;
-                                }
+                            if let Some(val) = metadata_value(name, &mut state.report) {
+                                new_bank.on_time = val;
                             }
                         } else if name.contains("offtime") {
-                            if let Some(idx) = name.find(":") {
-                                if let Ok(val) = &name[(idx + 1)..].parse() {
-                                    new_bank.off_time = *val;
-                                }
+                            if let Some(val) = metadata_value(name, &mut state.report) {
+                                new_bank.off_time = val;
                             }
                         } else if name.contains("disptime") {
-                            if let Some(idx) = name.find(":") {
-                                if let Ok(val) = &name[(idx + 1)..].parse() {
-                                    new_bank.disp_time = *val;
-                                }
+                            if let Some(val) = metadata_value(name, &mut state.report) {
+                                new_bank.disp_time = val;
                             }
                         } else if name.contains("properties") {
                             node.parse_properties(&mut new_bank.properties);
@@ -1355,11 +2211,9 @@ trait ParseCtx<'a> {
                     new_point.normal = norm.try_into().unwrap_or_default();
 
                     for (_, name) in node_children_with_keyword(node, "parent") {
-                        if let Some(idx) = name.find(":") {
-                            if let Ok(val) = &name[(idx + 1)..].parse() {
-                                new_point.attached_subobj = Some(ObjectId(*val));
-                                break;
-                            }
+                        if let Some(val) = metadata_value(name, &mut state.report) {
+                            new_point.attached_subobj = Some(ObjectId(val));
+                            break;
                         }
                     }
 
@@ -1368,9 +2222,23 @@ trait ParseCtx<'a> {
             } else if name == "#visual-center" {
                 let (pos, _, _) = node.parse_point(&transform, up);
                 model.visual_center = pos;
+            } else if name.starts_with('#') {
+                state.report.record(Some(name), "metadata node isn't recognized here, so it was ignored");
+            } else {
+                state
+                    .report
+                    .record(Some(name), "has no geometry and isn't a recognized metadata node, so it was ignored");
             }
         }
 
+        let sub_objects = &model.sub_objects;
+        let trees = map_maybe_parallel(std::mem::take(&mut state.subobj_polygons), |(obj_id, polygons)| {
+            (obj_id, BspData::recalculate(&sub_objects[obj_id].bsp_data.verts, polygons.into_iter()))
+        });
+        for (obj_id, tree) in trees {
+            model.sub_objects[obj_id].bsp_data.collision_tree = tree;
+        }
+
         for i in 0..model.sub_objects.len() {
             if let Some(parent) = model.sub_objects[ObjectId(i as u32)].parent {
                 let id = model.sub_objects[ObjectId(i as u32)].obj_id;
@@ -1391,6 +2259,7 @@ trait ParseCtx<'a> {
         model.header.bbox = model.recalc_bbox();
         model.recalc_mass();
         model.recalc_moi();
+        Ok(())
     }
 }
 
@@ -1425,11 +2294,12 @@ impl<'a> ParseCtx<'a> for DaeContext<'a> {
     fn up(&self) -> UpAxis {
         self.up
     }
-    fn parse_geometry(&self, node: &&'a dae::Node, transform: &Mat4x4) -> (Vec<Vec3d>, Vec<Vec3d>, Vec<(TextureId, Vec<PolyVertex>)>) {
+    fn parse_geometry(&self, node: &&'a dae::Node, transform: &Mat4x4, report: &mut ImportReport) -> Result<Geometry, ImportError> {
         let mut vertices_out: Vec<Vec3d> = vec![];
         let mut normals_out: Vec<Vec3d> = vec![];
         let mut normals_map: HashMap<Vec3d, NormalId> = HashMap::new();
         let mut polygons_out = vec![];
+        let node_name = node.name.as_deref().unwrap_or("an unnamed node");
 
         for geo in &node.instance_geometry {
             use dae::source::{SourceReader, ST, XYZ};
@@ -1457,25 +2327,44 @@ impl<'a> ParseCtx<'a> for DaeContext<'a> {
                     }
                 }
             }
-            let instance_mat_map: HashMap<_, _> = geo
-                .instance_materials()
-                .iter()
-                .map(|x| {
-                    (
-                        x.symbol.clone(),
-                        match &x.target.val {
-                            dae::Url::Fragment(r) => r.clone(),
-                            dae::Url::Other(_) => panic!(),
-                        },
-                    )
-                })
-                .collect();
+            let malformed = |what: &str| ImportError::Malformed(format!("the geometry of {} {}", node_name, what));
+
+            let mut instance_mat_map = HashMap::new();
+            for x in geo.instance_materials() {
+                match &x.target.val {
+                    dae::Url::Fragment(r) => instance_mat_map.insert(x.symbol.clone(), r.clone()),
+                    dae::Url::Other(_) => return Err(malformed("refers to a material in another document")),
+                };
+            }
+            let material_texture = |material: &Option<String>, report: &mut ImportReport| match material {
+                Some(mat) => match instance_mat_map.get(mat).and_then(|mat| self.material_map.get(mat)) {
+                    Some(&texture) => texture,
+                    None => {
+                        report.record(Some(node_name), format!("material {} is not bound to anything, so its faces were left untextured", mat));
+                        TextureId::UNTEXTURED
+                    }
+                },
+                None => TextureId::UNTEXTURED,
+            };
 
-            let geo = self.local_maps[&geo.url].element.as_mesh().unwrap();
-            let verts = geo.vertices.as_ref().unwrap().importer(&self.local_maps).unwrap();
+            let geo = self
+                .local_maps
+                .get(&geo.url)
+                .ok_or_else(|| malformed("refers to a mesh which doesn't exist"))?;
+            let Some(geo) = geo.element.as_mesh() else {
+                report.record(Some(node_name), "has geometry which isn't a mesh, so it was skipped");
+                continue;
+            };
+            let verts = geo
+                .vertices
+                .as_ref()
+                .ok_or_else(|| malformed("has no vertices"))?
+                .importer(&self.local_maps)
+                .map_err(|()| malformed("refers to vertex data which doesn't exist"))?;
             let mut vert_ctx = VertexContext { vertex_offset: vertices_out.len() as u32, normal_ids: vec![] };
 
-            for position in Clone::clone(verts.position_importer().unwrap()).map(Vec3d::from) {
+            let positions = verts.position_importer().ok_or_else(|| malformed("has no vertex positions"))?;
+            for position in Clone::clone(positions).map(Vec3d::from) {
                 vertices_out.push((transform * position).from_coord(self.up));
             }
 
@@ -1483,12 +2372,11 @@ impl<'a> ParseCtx<'a> for DaeContext<'a> {
                 match prim_elem {
                     dae::Primitive::PolyList(polies) => {
                         //println!("{:#?}, {:#?}, {:#?}", polies.material, instance_mat_map, self.material_map);
-                        let texture = match &polies.material {
-                            Some(mat) => self.material_map[&instance_mat_map[mat]],
-                            None => TextureId::UNTEXTURED,
-                        };
+                        let texture = material_texture(&polies.material, report);
 
-                        let importer = polies.importer(&self.local_maps, verts.clone()).unwrap();
+                        let importer = polies
+                            .importer(&self.local_maps, verts.clone())
+                            .map_err(|()| malformed("refers to polygon data which doesn't exist"))?;
 
                         vert_ctx.normal_ids = vec![];
                         if let Some(normal_importer) = importer.normal_importer() {
@@ -1504,17 +2392,19 @@ impl<'a> ParseCtx<'a> for DaeContext<'a> {
                         let mut iter = importer.read::<_, PolyVertex>(&vert_ctx, &polies.data.prim);
 
                         for &n in &*polies.data.vcount {
-                            let verts = (0..n).map(|_| iter.next().unwrap()).collect();
+                            let verts = (0..n)
+                                .map(|_| iter.next())
+                                .collect::<Option<_>>()
+                                .ok_or_else(|| malformed("has a truncated polygon list"))?;
                             polygons_out.push((texture, verts));
                         }
                     }
                     dae::Primitive::Triangles(tris) => {
                         //println!("{:#?}, {:#?}, {:#?}", tris.material, instance_mat_map, self.material_map);
-                        let texture = match &tris.material {
-                            Some(mat) => self.material_map[&instance_mat_map[mat]],
-                            None => TextureId::UNTEXTURED,
-                        };
-                        let importer = tris.importer(&self.local_maps, verts.clone()).unwrap();
+                        let texture = material_texture(&tris.material, report);
+                        let importer = tris
+                            .importer(&self.local_maps, verts.clone())
+                            .map_err(|()| malformed("refers to triangle data which doesn't exist"))?;
 
                         vert_ctx.normal_ids = vec![];
                         if let Some(normal_importer) = importer.normal_importer() {
@@ -1527,12 +2417,16 @@ impl<'a> ParseCtx<'a> for DaeContext<'a> {
                             }
                         }
 
-                        let mut iter = importer.read::<_, PolyVertex>(&vert_ctx, tris.data.prim.as_ref().unwrap());
+                        let Some(prim) = &tris.data.prim else { continue };
+                        let mut iter = importer.read::<_, PolyVertex>(&vert_ctx, prim);
                         while let Some(vert1) = iter.next() {
-                            polygons_out.push((texture, vec![vert1, iter.next().unwrap(), iter.next().unwrap()]));
+                            let (Some(vert2), Some(vert3)) = (iter.next(), iter.next()) else {
+                                return Err(malformed("has a truncated triangle list"));
+                            };
+                            polygons_out.push((texture, vec![vert1, vert2, vert3]));
                         }
                     }
-                    _ => {}
+                    _ => report.record(Some(node_name), "has primitives other than triangles and polygons, which were skipped"),
                 }
             }
         }
@@ -1541,12 +2435,13 @@ impl<'a> ParseCtx<'a> for DaeContext<'a> {
             poly.1.reverse(); // normal facing (which is determined by winding order) is inverted for FSO
         }
 
-        (vertices_out, normals_out, polygons_out)
+        Ok((vertices_out, normals_out, polygons_out))
     }
 }
 
-pub fn parse_dae(path: std::path::PathBuf) -> Model {
-    let document = dae::Document::from_file(&path).unwrap();
+/// Imports a COLLADA file, also returning a list of the nodes which couldn't be imported as they were
+pub fn parse_dae(path: std::path::PathBuf) -> Result<(Model, ImportReport), ImportError> {
+    let document = dae::Document::from_file(&path).map_err(ImportError::Dae)?;
     // use std::io::Write;
     // write!(std::fs::File::create("output.log").unwrap(), "{:#?}", document).unwrap();
     let mut ctx = DaeContext {
@@ -1556,8 +2451,10 @@ pub fn parse_dae(path: std::path::PathBuf) -> Model {
     };
 
     document.for_each(|material: &dae::Material| {
-        ctx.material_map
-            .insert(material.id.as_ref().unwrap().clone(), TextureId(ctx.material_map.len() as u32));
+        // a material without an id can't be referred to anyway
+        if let Some(id) = &material.id {
+            ctx.material_map.insert(id.clone(), TextureId(ctx.material_map.len() as u32));
+        }
     });
 
     let mut model = Model::default();
@@ -1567,12 +2464,16 @@ pub fn parse_dae(path: std::path::PathBuf) -> Model {
         model.textures[id.0 as usize] = tex.strip_suffix("-material").unwrap_or(tex).to_string();
     }
 
-    let scene = &document.scene.as_ref().unwrap().instance_visual_scene.as_ref().unwrap().url;
-    ctx.parse_top_level_nodes(&mut model, &ctx.local_maps.get(scene).unwrap().nodes);
+    let scene = (document.scene.as_ref())
+        .and_then(|scene| scene.instance_visual_scene.as_ref())
+        .and_then(|scene| ctx.local_maps.get(&scene.url))
+        .ok_or_else(|| ImportError::Malformed(format!("no visual scene found")))?;
+    let mut state = ImportState::default();
+    ctx.parse_top_level_nodes(&mut model, &scene.nodes, &mut state)?;
 
     model.prune_unused_textures();
 
-    model
+    Ok((model, state.report))
 }
 
 // =================================================================
@@ -1601,18 +2502,30 @@ struct GltfContext {
 
 impl<'a> ParseCtx<'a> for GltfContext {
     type Node = gltf::Node<'a>;
-    fn parse_geometry(&self, node: &Self::Node, transform: &Mat4x4) -> (Vec<Vec3d>, Vec<Vec3d>, Vec<(TextureId, Vec<PolyVertex>)>) {
+    fn parse_geometry(&self, node: &Self::Node, transform: &Mat4x4, report: &mut ImportReport) -> Result<Geometry, ImportError> {
         let mut vertices_out: Vec<Vec3d> = vec![];
         let mut vertices_map: HashMap<Vec3d, VertexId> = HashMap::new();
         let mut normals_out: Vec<Vec3d> = vec![];
         let mut normals_map: HashMap<Vec3d, NormalId> = HashMap::new();
         let mut polygons_out = vec![];
+        let node_name = node.name().unwrap_or("an unnamed node");
+        let malformed = |what: &str| ImportError::Malformed(format!("the mesh of {} {}", node_name, what));
 
         if let Some(mesh) = node.mesh() {
             for primitive in mesh.primitives() {
-                let reader = primitive.reader(|b| Some(&self.buffers[b.index()]));
+                if primitive.mode() != gltf::mesh::Mode::Triangles {
+                    report
+                        .record(Some(node_name), format!("has {:?} primitives, which were skipped; only triangles are supported", primitive.mode()));
+                    continue;
+                }
+
+                let reader = primitive.reader(|b| self.buffers.get(b.index()).map(|data| &data[..]));
                 let mut vertex_ids = vec![];
-                for position in reader.read_positions().unwrap().map(Vec3d::from) {
+                for position in reader
+                    .read_positions()
+                    .ok_or_else(|| malformed("has no vertex positions"))?
+                    .map(Vec3d::from)
+                {
                     vertex_ids.push(*vertices_map.entry(position).or_insert_with(|| {
                         let id = VertexId(vertices_out.len().try_into().unwrap());
                         vertices_out.push((transform * position).from_coord(UpAxis::YUp));
@@ -1627,38 +2540,34 @@ impl<'a> ParseCtx<'a> for GltfContext {
                     .read_tex_coords(0)
                     .map(|iter| iter.into_f32().map(|[u, v]| (u, v)).collect::<Vec<_>>());
 
-                match primitive.mode() {
-                    gltf::mesh::Mode::Triangles => {
-                        let mut normal_ids = vec![];
-                        if let Some(normal_iter) = reader.read_normals() {
-                            for normal in normal_iter.map(Vec3d::from) {
-                                normal_ids.push(*normals_map.entry(normal).or_insert_with(|| {
-                                    let id = NormalId(normals_out.len().try_into().unwrap());
-                                    normals_out.push((transform * normal).from_coord(UpAxis::YUp));
-                                    id
-                                }));
-                            }
-                        }
-
-                        macro_rules! on_indices {
-                            ($indices:expr) => {{
-                                let mut iter = $indices.map(|i| PolyVertex {
-                                    vertex_id: vertex_ids[i as usize],
-                                    normal_id: normal_ids.get(i as usize).copied().unwrap_or_default(),
-                                    uv: uvs.as_ref().map_or((0., 0.), |vec| vec[i as usize]),
-                                });
-                                while let Some(vert1) = iter.next() {
-                                    polygons_out.push((texture, vec![vert1, iter.next().unwrap(), iter.next().unwrap()]));
-                                }
-                            }};
-                        }
-                        match reader.read_indices() {
-                            Some(indices) => on_indices!(indices.into_u32()),
-                            None => on_indices!(0..vertex_ids.len() as u32),
-                        }
+                let mut normal_ids = vec![];
+                if let Some(normal_iter) = reader.read_normals() {
+                    for normal in normal_iter.map(Vec3d::from) {
+                        normal_ids.push(*normals_map.entry(normal).or_insert_with(|| {
+                            let id = NormalId(normals_out.len().try_into().unwrap());
+                            normals_out.push((transform * normal).from_coord(UpAxis::YUp));
+                            id
+                        }));
                     }
-                    _ => unreachable!(),
                 }
+
+                let indices: Vec<u32> = match reader.read_indices() {
+                    Some(indices) => indices.into_u32().collect(),
+                    None => (0..vertex_ids.len() as u32).collect(),
+                };
+                if indices.len() % 3 != 0 {
+                    return Err(malformed("has a truncated triangle list"));
+                }
+                let mut verts = Vec::with_capacity(indices.len());
+                for i in indices {
+                    let i = i as usize;
+                    verts.push(PolyVertex {
+                        vertex_id: *vertex_ids.get(i).ok_or_else(|| malformed("refers to a vertex which doesn't exist"))?,
+                        normal_id: normal_ids.get(i).copied().unwrap_or_default(),
+                        uv: uvs.as_ref().and_then(|vec| vec.get(i).copied()).unwrap_or((0., 0.)),
+                    });
+                }
+                polygons_out.extend(verts.chunks(3).map(|tri| (texture, tri.to_vec())));
             }
         }
 
@@ -1666,22 +2575,32 @@ impl<'a> ParseCtx<'a> for GltfContext {
             poly.1.reverse(); // normal facing (which is determined by winding order) is inverted for FSO
         }
 
-        (vertices_out, normals_out, polygons_out)
+        Ok((vertices_out, normals_out, polygons_out))
     }
 }
 
-pub fn parse_gltf(path: std::path::PathBuf) -> Model {
+/// Imports a glTF file, also returning a list of the nodes which couldn't be imported as they were
+pub fn parse_gltf(path: std::path::PathBuf) -> Result<(Model, ImportReport), ImportError> {
     let base = path.parent().unwrap_or_else(|| std::path::Path::new("./"));
-    let reader = BufReader::new(File::open(&path).unwrap());
-    let gltf::Gltf { document: gltf, blob } = gltf::Gltf::from_reader(reader).unwrap();
+    let reader = BufReader::new(File::open(&path)?);
+    let gltf::Gltf { document: gltf, blob } = gltf::Gltf::from_reader(reader).map_err(ImportError::Gltf)?;
     // let buffers = gltf::import::import_buffer_data(&gltf, Some(base), blob).unwrap();
-    let buffers = import_buffer_data(&gltf, &base, blob);
+    let buffers = import_buffer_data(&gltf, base, blob)?;
+    let mut state = ImportState::default();
     let mut model = Model::default();
     model.path_to_file = path.canonicalize().unwrap_or(path);
     model.textures = gltf
         .materials()
-        .map(|mat| {
-            let mut name = mat.name().unwrap().strip_suffix("-material").unwrap_or(mat.name().unwrap());
+        .enumerate()
+        .map(|(i, mat)| {
+            let Some(name) = mat.name() else {
+                let name = format!("material{}", i);
+                state
+                    .report
+                    .record(None, format!("material {} has no name, so it was called {}", i, name));
+                return name;
+            };
+            let mut name = name.strip_suffix("-material").unwrap_or(name);
             if let Some(idx) = name.find(".") {
                 name = &name[0..idx];
             }
@@ -1691,55 +2610,59 @@ pub fn parse_gltf(path: std::path::PathBuf) -> Model {
 
     let scene = gltf
         .default_scene()
-        .unwrap_or_else(|| gltf.scenes().next().expect("no scene found in gltf file!"));
+        .or_else(|| gltf.scenes().next())
+        .ok_or_else(|| ImportError::Malformed(format!("no scene found")))?;
 
-    GltfContext { buffers }.parse_top_level_nodes(&mut model, scene.nodes());
+    GltfContext { buffers }.parse_top_level_nodes(&mut model, scene.nodes(), &mut state)?;
 
     model.prune_unused_textures();
 
-    model
+    Ok((model, state.report))
 }
 
 // Polyfill for https://github.com/gltf-rs/gltf/pull/341 - delete me
-fn import_buffer_data(document: &gltf::Document, base: &std::path::Path, mut blob: Option<Vec<u8>>) -> Vec<gltf::buffer::Data> {
-    fn read_to_end(path: impl AsRef<std::path::Path>) -> Vec<u8> {
-        let file = File::open(path.as_ref()).unwrap();
+fn import_buffer_data(document: &gltf::Document, base: &std::path::Path, mut blob: Option<Vec<u8>>) -> Result<Vec<gltf::buffer::Data>, ImportError> {
+    fn read_to_end(path: impl AsRef<std::path::Path>) -> io::Result<Vec<u8>> {
+        let file = File::open(path.as_ref())?;
         let length = file.metadata().map(|x| x.len() + 1).unwrap_or(0);
         let mut reader = io::BufReader::new(file);
         let mut data = Vec::with_capacity(length as usize);
-        reader.read_to_end(&mut data).unwrap();
-        data
+        reader.read_to_end(&mut data)?;
+        Ok(data)
     }
 
     let mut buffers = Vec::new();
     for buffer in document.buffers() {
+        let malformed = |what: &str| ImportError::Malformed(format!("buffer {} {}", buffer.index(), what));
         let mut data = match buffer.source() {
             gltf::buffer::Source::Uri(uri) => {
                 if uri.contains(':') {
                     if let Some(rest) = uri.strip_prefix("data:") {
                         let mut it = rest.split(";base64,");
                         match (it.next(), it.next()) {
-                            (_, Some(base64)) | (Some(base64), _) => base64::decode(&base64).unwrap(),
-                            _ => panic!(),
+                            (_, Some(base64)) | (Some(base64), _) => base64::decode(base64).map_err(|_| malformed("has invalid base64 data"))?,
+                            _ => return Err(malformed("has an empty data uri")),
                         }
                     } else if let Some(rest) = uri.strip_prefix("file://") {
-                        read_to_end(rest)
+                        read_to_end(rest)?
                     } else if let Some(rest) = uri.strip_prefix("file:") {
-                        read_to_end(rest)
+                        read_to_end(rest)?
                     } else {
-                        panic!()
+                        return Err(malformed(&format!("has an unsupported uri {}", uri)));
                     }
                 } else {
-                    read_to_end(base.join(&*urlencoding::decode(uri).unwrap()))
+                    read_to_end(base.join(&*urlencoding::decode(uri).map_err(|_| malformed("has an invalid uri"))?))?
                 }
             }
-            gltf::buffer::Source::Bin => blob.take().unwrap(),
+            gltf::buffer::Source::Bin => blob.take().ok_or_else(|| malformed("refers to a binary chunk which isn't there"))?,
         };
         while data.len() % 4 != 0 {
             data.push(0);
         }
-        assert!(data.len() >= buffer.length());
+        if data.len() < buffer.length() {
+            return Err(malformed(&format!("is {} bytes long, but should be {}", data.len(), buffer.length())));
+        }
         buffers.push(gltf::buffer::Data(data));
     }
-    buffers
+    Ok(buffers)
 }
@@ -0,0 +1,314 @@
+//! A typed view of the `$key=value` property strings that subobjects, special points, thruster banks, glow banks and docking bays carry,
+//! along with the keys FSO understands for each of them.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::Vec3d;
+
+/// What a properties string belongs to, which decides which keys FSO looks for in it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PropertiesKind {
+    SubObject,
+    SpecialPoint,
+    ThrusterBank,
+    GlowBank,
+    DockingBay,
+}
+
+/// The kind of value a property takes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropertyType {
+    /// only the key, no value
+    Flag,
+    Text,
+    Float,
+    Int,
+    /// three comma separated numbers
+    Vector,
+    /// one of a fixed set of words
+    Choice(&'static [&'static str]),
+}
+impl PropertyType {
+    /// Parses `value` as this type, or `None` if it isn't one
+    pub fn parse(self, value: &str) -> Option<PropertyValue> {
+        let value = value.trim();
+        match self {
+            PropertyType::Flag => Some(PropertyValue::Flag),
+            PropertyType::Text => (!value.is_empty()).then(|| PropertyValue::Text(value.to_string())),
+            PropertyType::Float => value.parse().ok().map(PropertyValue::Float),
+            PropertyType::Int => value.parse().ok().map(PropertyValue::Int),
+            PropertyType::Vector => Vec3d::from_str(value).ok().map(PropertyValue::Vector),
+            PropertyType::Choice(choices) => choices.contains(&value).then(|| PropertyValue::Text(value.to_string())),
+        }
+    }
+
+    /// What a newly added property of this type starts out as
+    pub fn default_value(self) -> Option<String> {
+        match self {
+            PropertyType::Flag => None,
+            PropertyType::Text => Some(String::new()),
+            PropertyType::Float | PropertyType::Int => Some("0".to_string()),
+            PropertyType::Vector => Some(Vec3d::ZERO.to_string()),
+            PropertyType::Choice(choices) => Some(choices[0].to_string()),
+        }
+    }
+}
+
+/// A parsed property value, see [`PropertyType::parse`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyValue {
+    Flag,
+    Text(String),
+    Float(f32),
+    Int(i32),
+    Vector(Vec3d),
+}
+
+/// A property FSO understands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PropertyDef {
+    pub key: &'static str,
+    pub ty: PropertyType,
+    pub doc: &'static str,
+}
+
+const fn def(key: &'static str, ty: PropertyType, doc: &'static str) -> PropertyDef {
+    PropertyDef { key, ty, doc }
+}
+
+const SUBOBJECT_PROPERTIES: &[PropertyDef] = &[
+    def("$special", PropertyType::Choice(&["subsystem"]), "Makes the subobject a subsystem, which can be targeted and destroyed"),
+    def("$name", PropertyType::Text, "The name of the subsystem, instead of the subobject's"),
+    def("$fov", PropertyType::Float, "For turret guns, the angle in degrees of the cone they can fire in"),
+    def("$max_fov", PropertyType::Float, "For turret guns, how far in degrees they can elevate from the base"),
+    def("$base_fov", PropertyType::Float, "For turret bases, the angle in degrees they can turn through"),
+    def("$uvec", PropertyType::Vector, "The up direction of the turret, if it doesn't point along an axis"),
+    def("$fvec", PropertyType::Vector, "The forward direction of the turret, if it doesn't point along an axis"),
+    def("$crewspot", PropertyType::Flag, "Marks the turret as a crew position"),
+    def("$gun_rotation", PropertyType::Flag, "Spins the turret's barrels as it fires"),
+    def("$rotate", PropertyType::Float, "Rotates the subsystem constantly, taking this many seconds per turn"),
+    def("$dumb_rotate", PropertyType::Float, "Rotates the subobject constantly without it being a subsystem, taking this many seconds per turn"),
+    def("$triggered", PropertyType::Flag, "Rotates only when an animation is triggered"),
+    def("$stepped", PropertyType::Flag, "Rotates in steps instead of smoothly"),
+    def("$steps", PropertyType::Int, "For stepped rotation, how many steps it takes per turn"),
+    def("$t_paused", PropertyType::Float, "For stepped rotation, how many seconds it pauses at each step"),
+    def("$t_transit", PropertyType::Float, "For stepped rotation, how many seconds it takes to get to the next step"),
+    def("$fixed_rotation", PropertyType::Flag, "Rotates at a fixed rate, without speeding up or slowing down"),
+    def("$look_at", PropertyType::Text, "The subobject this one turns to keep facing"),
+    def("$no_collisions", PropertyType::Flag, "Keeps anything from colliding with the subobject or its children"),
+    def("$nocollide_this_only", PropertyType::Flag, "Keeps anything from colliding with the subobject, but not its children"),
+    def("$collide_invisible", PropertyType::Flag, "Keeps the subobject solid even when it isn't drawn"),
+    def("$attach_thrusters", PropertyType::Flag, "Moves the thrusters along with the subobject"),
+    def("$detail_box", PropertyType::Int, "Only draws the subobject while the camera is inside (1) or outside (-1) the box"),
+    def("$box_min", PropertyType::Vector, "The minimum corner of the detail box"),
+    def("$box_max", PropertyType::Vector, "The maximum corner of the detail box"),
+    def("$box_offset", PropertyType::Vector, "Moves the detail box"),
+    def("$detail_sphere", PropertyType::Int, "Only draws the subobject while the camera is inside (1) or outside (-1) the sphere"),
+    def("$radius", PropertyType::Float, "The radius of the detail sphere"),
+    def("$sphere_offset", PropertyType::Vector, "Moves the detail sphere"),
+    def("$do_not_scale_detail_distances", PropertyType::Flag, "Keeps the detail level distances the same at every graphics setting"),
+];
+
+const SPECIAL_POINT_PROPERTIES: &[PropertyDef] = &[
+    def("$special", PropertyType::Choice(&["subsystem", "shieldpoint"]), "What the special point is for"),
+    def("$name", PropertyType::Text, "The name of the subsystem, instead of the special point's"),
+];
+
+const THRUSTER_BANK_PROPERTIES: &[PropertyDef] = &[def(
+    "$engine_subsystem",
+    PropertyType::Text,
+    "The engine subsystem whose destruction puts out these thrusters",
+)];
+
+const GLOW_BANK_PROPERTIES: &[PropertyDef] = &[def("$glow_texture", PropertyType::Text, "The texture drawn at each of the glow points")];
+
+const DOCKING_BAY_PROPERTIES: &[PropertyDef] = &[
+    def("$name", PropertyType::Text, "The name of the docking bay"),
+    def("$parent_submodel", PropertyType::Text, "The subobject the docking bay moves along with"),
+];
+
+impl PropertiesKind {
+    /// Every key FSO reads for this kind of item
+    pub fn known_properties(self) -> &'static [PropertyDef] {
+        match self {
+            PropertiesKind::SubObject => SUBOBJECT_PROPERTIES,
+            PropertiesKind::SpecialPoint => SPECIAL_POINT_PROPERTIES,
+            PropertiesKind::ThrusterBank => THRUSTER_BANK_PROPERTIES,
+            PropertiesKind::GlowBank => GLOW_BANK_PROPERTIES,
+            PropertiesKind::DockingBay => DOCKING_BAY_PROPERTIES,
+        }
+    }
+
+    pub fn find(self, key: &str) -> Option<&'static PropertyDef> {
+        self.known_properties().iter().find(|def| def.key == key)
+    }
+
+    /// The known key closest to an unknown one, if it's close enough that it's probably a typo of it
+    pub fn find_similar(self, key: &str) -> Option<&'static PropertyDef> {
+        (self.known_properties().iter())
+            .map(|def| (def, edit_distance(key, def.key)))
+            .filter(|&(_, distance)| distance <= 2)
+            .min_by_key(|&(_, distance)| distance)
+            .map(|(def, _)| def)
+    }
+}
+
+// the number of single character insertions, deletions, substitutions and swaps of neighbours it takes to turn one into the other
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b) = (a.chars().collect::<Vec<_>>(), b.chars().collect::<Vec<_>>());
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in rows[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            rows[i][j] = (rows[i - 1][j] + 1).min(rows[i][j - 1] + 1).min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                rows[i][j] = rows[i][j].min(rows[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// One line of a properties string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Property {
+    pub key: String,
+    /// whatever was between the key and the value, kept so untouched lines are written back as they were
+    pub separator: String,
+    /// `None` for a flag
+    pub value: Option<String>,
+}
+impl Display for Property {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.value {
+            Some(value) => write!(f, "{}{}{}", self.key, self.separator, value),
+            None => write!(f, "{}{}", self.key, self.separator),
+        }
+    }
+}
+
+/// Something wrong with a properties string, as found by [`Properties::validate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PropertyIssue {
+    /// a key FSO doesn't read for this kind of item, and the known one it's probably a typo of
+    UnknownKey { key: String, suggestion: Option<&'static str> },
+    /// a value FSO can't make sense of for its key
+    InvalidValue { key: String, value: String },
+    /// a key that needs a value, without one
+    MissingValue { key: String },
+}
+impl Display for PropertyIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PropertyIssue::UnknownKey { key, suggestion: Some(suggestion) } => write!(f, "unknown key '{}', did you mean '{}'?", key, suggestion),
+            PropertyIssue::UnknownKey { key, suggestion: None } => write!(f, "unknown key '{}'", key),
+            PropertyIssue::InvalidValue { key, value } => write!(f, "invalid value '{}' for '{}'", value, key),
+            PropertyIssue::MissingValue { key } => write!(f, "no value for '{}'", key),
+        }
+    }
+}
+
+/// A properties string split into its lines, in order, which writes back out the same apart from blank lines
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Properties {
+    pub entries: Vec<Property>,
+}
+impl Properties {
+    pub fn parse(text: &str) -> Self {
+        let entries = (text.split(|c: char| c.is_ascii_control()))
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let key_end = line.find(|c: char| c == '=' || c == ':' || c.is_whitespace()).unwrap_or(line.len());
+                let (key, rest) = line.split_at(key_end);
+                let value = rest.trim_start_matches(|c: char| c == '=' || c == ':' || c.is_whitespace());
+                Property {
+                    key: key.to_string(),
+                    separator: rest[..rest.len() - value.len()].to_string(),
+                    value: (!value.is_empty()).then(|| value.to_string()),
+                }
+            })
+            .collect();
+        Properties { entries }
+    }
+
+    /// The raw value of the first line with this key, which is empty for a flag
+    pub fn get(&self, key: &str) -> Option<&str> {
+        let entry = self.entries.iter().find(|entry| entry.key == key)?;
+        Some(entry.value.as_deref().unwrap_or_default())
+    }
+
+    /// The value of a key this kind of item has, parsed as its type, or `None` if it's missing or invalid
+    pub fn get_typed(&self, kind: PropertiesKind, key: &str) -> Option<PropertyValue> {
+        kind.find(key)?.ty.parse(self.get(key)?)
+    }
+
+    /// Sets the value of the first line with this key, or adds a line for it; a `None` value makes it a flag
+    pub fn set(&mut self, key: &str, value: Option<&str>) {
+        let value = value.map(str::to_string);
+        match self.entries.iter_mut().find(|entry| entry.key == key) {
+            Some(entry) => {
+                if value.is_none() {
+                    entry.separator.clear();
+                } else if entry.separator.is_empty() {
+                    entry.separator = "=".to_string();
+                }
+                entry.value = value;
+            }
+            None => {
+                let separator = if value.is_some() { "=" } else { "" };
+                self.entries.push(Property {
+                    key: key.to_string(),
+                    separator: separator.to_string(),
+                    value,
+                });
+            }
+        }
+    }
+
+    /// Removes every line with this key, returning whether there were any
+    pub fn remove(&mut self, key: &str) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|entry| entry.key != key);
+        self.entries.len() != len
+    }
+
+    /// Checks every line against the keys this kind of item has
+    pub fn validate(&self, kind: PropertiesKind) -> Vec<PropertyIssue> {
+        let mut issues = vec![];
+        for entry in &self.entries {
+            match kind.find(&entry.key) {
+                None => issues.push(PropertyIssue::UnknownKey {
+                    key: entry.key.clone(),
+                    suggestion: kind.find_similar(&entry.key).map(|def| def.key),
+                }),
+                Some(def) => match &entry.value {
+                    None if def.ty != PropertyType::Flag => issues.push(PropertyIssue::MissingValue { key: entry.key.clone() }),
+                    Some(value) if def.ty.parse(value).is_none() => {
+                        issues.push(PropertyIssue::InvalidValue { key: entry.key.clone(), value: value.clone() })
+                    }
+                    _ => {}
+                },
+            }
+        }
+        issues
+    }
+}
+impl Display for Properties {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, entry) in self.entries.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", entry)?;
+        }
+        Ok(())
+    }
+}
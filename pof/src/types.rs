@@ -1,21 +1,24 @@
 use std::cell::Cell;
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::convert::TryFrom;
 use std::fmt::{Debug, Display};
 use std::io::{self, Write};
 use std::ops::{Add, AddAssign, Deref, DerefMut, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign};
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::OnceLock;
 
 use itertools::Itertools;
 
 use byteorder::{WriteBytesExt, LE};
 pub use dae_parser::UpAxis;
 use glm::{TMat3, TMat4, Vec3};
-use nalgebra::Matrix3;
+use nalgebra::{Matrix3, Vector3};
 use nalgebra_glm::Mat4;
 extern crate nalgebra_glm as glm;
 
+use crate::geometry;
+use crate::properties::{Properties, PropertiesKind};
 use crate::write::Serialize;
 
 macro_rules! id_type {
@@ -58,8 +61,35 @@ pub(crate) fn get_version() -> Version {
     VERSION.with(|f| f.get())
 }
 
+// BSP work is independent per subobject, so with the `parallel` feature it is spread across threads
+// results always come back in the original order, so the output is identical either way
+// note that worker threads don't see VERSION, so nothing run through these may depend on it
+pub(crate) fn map_maybe_parallel<T: Send, U: Send>(items: Vec<T>, f: impl Fn(T) -> U + Send + Sync) -> Vec<U> {
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        items.into_par_iter().map(f).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        items.into_iter().map(f).collect()
+    }
+}
+
+pub(crate) fn for_each_maybe_parallel<T: Send>(items: &mut [T], f: impl Fn(&mut T) + Send + Sync) {
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        items.par_iter_mut().for_each(f)
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        items.iter_mut().for_each(f)
+    }
+}
+
 // like a regular vector, but indexed with ObjectIds only, for some safety
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ObjVec<T>(pub Vec<T>);
 impl<T> Index<ObjectId> for ObjVec<T> {
     type Output = T;
@@ -123,8 +153,9 @@ macro_rules! mk_struct {
     };
 }
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum Axis {
+    #[default]
     X,
     Y,
     Z,
@@ -154,6 +185,36 @@ impl std::hash::Hash for Vec3d {
         self.z.to_bits().hash(state);
     }
 }
+/// An `f32` with a total order, so it can go in a [`Warning`]
+#[derive(Clone, Copy, Default)]
+pub struct OrdF32(pub f32);
+impl PartialEq for OrdF32 {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
+impl Eq for OrdF32 {}
+impl PartialOrd for OrdF32 {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for OrdF32 {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+impl Debug for OrdF32 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+impl Display for OrdF32 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
 impl Debug for Vec3d {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "({}, {}, {})", &self.x, &self.y, &self.z)
@@ -433,6 +494,13 @@ pub fn mat4_rotation_only(matrix: &TMat4<f32>) -> TMat4<f32> {
     TMat3::from_columns(&arr).to_homogeneous()
 }
 
+// the reflection across the plane through the origin perpendicular to the axis
+fn mirror_matrix(axis: Axis) -> TMat4<f32> {
+    let mut scale = Vec3d::new(1.0, 1.0, 1.0);
+    scale[axis] = -1.0;
+    glm::scaling(&scale.into())
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct NormalVec3(pub Vec3d);
 
@@ -505,6 +573,14 @@ impl BoundingBox {
             (self.max.x - self.min.x) * (self.max.y - self.min.y) * (self.max.z - self.min.z)
         }
     }
+    pub fn surface_area(&self) -> f32 {
+        if self.is_inverted() {
+            0.
+        } else {
+            let (x, y, z) = (self.x_width(), self.y_height(), self.z_length());
+            2. * (x * y + y * z + z * x)
+        }
+    }
     pub fn x_width(&self) -> f32 {
         self.max.x - self.min.x
     }
@@ -574,6 +650,12 @@ impl BoundingBox {
         true
     }
 
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        ALL_AXES
+            .into_iter()
+            .all(|axis| self.min[axis] <= other.max[axis] && other.min[axis] <= self.max[axis])
+    }
+
     /// Replaces `Self::EMPTY` with `Self::ZERO`.
     pub fn sanitize(&self) -> &Self {
         if *self == Self::EMPTY {
@@ -598,7 +680,7 @@ impl Serialize for BspLightKind {
 pub const MAX_EYES: usize = 9;
 
 mk_struct! {
-    #[derive(Debug)]
+    #[derive(Debug, Clone)]
     // this is pretty much unused by the engine
     pub struct BspLight {
         pub location: Vec3d,
@@ -725,7 +807,7 @@ impl Serialize for ShieldPolygon {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ShieldNode {
     Split {
         bbox: BoundingBox,
@@ -740,6 +822,41 @@ pub enum ShieldNode {
 impl ShieldNode {
     pub(crate) const SPLIT: u32 = 0;
     pub(crate) const LEAF: u32 = 1;
+
+    pub fn bbox(&self) -> &BoundingBox {
+        match self {
+            ShieldNode::Split { bbox, .. } | ShieldNode::Leaf { bbox, .. } => bbox,
+        }
+    }
+
+    pub fn sum_of_bboxes(&self) -> f32 {
+        match self {
+            ShieldNode::Split { bbox, front, back } => bbox.volume() + front.sum_of_bboxes() + back.sum_of_bboxes(),
+            ShieldNode::Leaf { bbox, .. } => bbox.volume(),
+        }
+    }
+
+    pub fn sum_depth_and_size(&self) -> (u32, u32) {
+        match self {
+            ShieldNode::Split { front, back, .. } => {
+                let (depth1, sz1) = front.sum_depth_and_size();
+                let (depth2, sz2) = back.sum_depth_and_size();
+                (depth1 + depth2 + sz1 + sz2, sz1 + sz2)
+            }
+            ShieldNode::Leaf { .. } => (0, 1),
+        }
+    }
+
+    pub fn max_depth(&self) -> u32 {
+        match self {
+            ShieldNode::Split { front, back, .. } => 1 + front.max_depth().max(back.max_depth()),
+            ShieldNode::Leaf { .. } => 0,
+        }
+    }
+
+    pub fn quality(&self) -> TreeQuality {
+        TreeQuality::new(self.sum_depth_and_size(), self.max_depth(), self.sum_of_bboxes(), self.bbox().volume())
+    }
 }
 impl Serialize for ShieldNode {
     fn write_to(&self, w: &mut impl Write) -> io::Result<()> {
@@ -901,7 +1018,7 @@ impl GlowPoint {
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct ObjHeader {
     pub max_radius: f32,
     pub obj_flags: u32,
@@ -915,14 +1032,318 @@ pub struct ObjHeader {
     pub bsp_lights: Vec<BspLight>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ShieldData {
     pub verts: Vec<Vec3d>,
     pub polygons: Vec<ShieldPolygon>,
     pub collision_tree: Option<ShieldNode>,
 }
 impl ShieldData {
+    /// Generates a shield enveloping the detail0 hierarchy: its convex hull, simplified down to at most `target_poly_count` triangles
+    /// (though never below a tetrahedron) and pushed out until it clears the hull by `padding` everywhere.
+    ///
+    /// Returns `None` if the model has no detail levels, or detail0 is too flat to enclose anything or has vertices that aren't finite.
+    pub fn generate_from_model(model: &Model, target_poly_count: usize, padding: f32) -> Option<ShieldData> {
+        let detail_0 = *model.header.detail_levels.first()?;
+        let points = (model.sub_objects.iter())
+            .filter(|subobj| model.is_obj_id_ancestor(subobj.obj_id, detail_0))
+            .flat_map(|subobj| {
+                let offset = model.get_total_subobj_offset(subobj.obj_id);
+                subobj.bsp_data.verts.iter().map(move |&vert| vert + offset)
+            })
+            .collect::<Vec<_>>();
+
+        let (hull_verts, hull_tris) = geometry::compact(&points, &geometry::convex_hull(&points)?);
+        let (mut verts, mut tris) = if hull_tris.len() > target_poly_count {
+            let tris = (geometry::decimate(&hull_verts, &hull_tris, target_poly_count.max(4), &[]).into_iter())
+                .map(|(_, tri)| tri)
+                .collect::<Vec<_>>();
+            // simplifying can leave shallow dents, taking the hull of whats left smooths them back out
+            let (verts, _) = geometry::compact(&hull_verts, &tris);
+            geometry::compact(&verts, &geometry::convex_hull(&verts)?)
+        } else {
+            (hull_verts.clone(), hull_tris)
+        };
+
+        // the simplified hull cuts corners off the real one, and the padding has to go on top of that
+        // so each face is pushed out by however far the real hull pokes past it, plus the padding
+        // moving the vertices tilts the faces a bit, so this is repeated (on the hull of the moved vertices, so it stays convex)
+        // until nothing pokes out anymore
+        for _ in 0..10 {
+            let face_normals = tris
+                .iter()
+                .map(|tri| {
+                    let [v1, v2, v3] = tri.map(|i| verts[i as usize]);
+                    (v2 - v1).cross(&(v3 - v1)).normalize()
+                })
+                .collect::<Vec<_>>();
+            let pushes = (tris.iter().zip(&face_normals))
+                .map(|(tri, normal)| {
+                    let plane_dist = normal.dot(&verts[tri[0] as usize]) - padding;
+                    hull_verts.iter().map(|vert| normal.dot(vert) - plane_dist).fold(0.0, f32::max)
+                })
+                .collect::<Vec<_>>();
+            if pushes.iter().all(|&push| push <= 0.001 * (1.0 + padding.abs())) {
+                break;
+            }
+
+            // move each vertex to where the planes of its faces meet once they've been pushed out
+            // smooth areas have nearly parallel faces which don't pin down much, so the vertex is also held loosely
+            // to just moving out along its normal, to keep it from sliding off sideways
+            let mut vert_faces = vec![vec![]; verts.len()];
+            for (i, tri) in tris.iter().enumerate() {
+                for &vert in tri {
+                    vert_faces[vert as usize].push(i);
+                }
+            }
+            for (vert, faces) in verts.iter_mut().zip(&vert_faces) {
+                let normal = faces.iter().fold(Vec3d::ZERO, |sum, &i| sum + face_normals[i]).normalize();
+                let average_push = faces.iter().map(|&i| pushes[i]).sum::<f32>() / faces.len() as f32;
+                let weight = 0.01 * faces.len() as f32;
+                let mut lhs = glm::Mat3::identity() * weight;
+                let mut rhs = Vec3::from(normal * average_push) * weight;
+                for &i in faces {
+                    let face_normal = Vec3::from(face_normals[i]);
+                    lhs += face_normal * face_normal.transpose();
+                    rhs += face_normal * pushes[i];
+                }
+                *vert += match lhs.try_inverse() {
+                    Some(inverse) => Vec3d::from(inverse * rhs),
+                    None => normal * average_push,
+                };
+            }
+            (verts, tris) = geometry::compact(&verts, &geometry::convex_hull(&verts)?);
+        }
+
+        let polygons = tris
+            .iter()
+            .map(|tri| {
+                let [v1, v2, v3] = tri.map(|i| verts[i as usize]);
+                ShieldPolygon {
+                    normal: (v2 - v1).cross(&(v3 - v1)).normalize(),
+                    verts: (VertexId(tri[0]), VertexId(tri[1]), VertexId(tri[2])),
+                    neighbors: Default::default(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut shield = ShieldData {
+            collision_tree: Some(ShieldData::recalculate_tree(&verts, &polygons)),
+            verts,
+            polygons,
+        };
+        shield.recalculate_neighbors();
+        Some(shield)
+    }
+
+    /// Fills in the neighbors of every polygon from the edges they share.
+    ///
+    /// Returns how many edges are open, and how many are shared by more than two polygons or wound inconsistently;
+    /// both should be 0 for a well formed shield.
+    pub fn recalculate_neighbors(&mut self) -> (usize, usize) {
+        // create a map keyed on each vertex pair, based on winding order, where the value is the polygon id
+        // an edge which is already in the map is shared by too many faces, or wound inconsistently
+        let mut map: HashMap<(VertexId, VertexId), PolygonId> = HashMap::new();
+        let mut overused_edges = 0;
+        for (i, poly) in self.polygons.iter().enumerate() {
+            for edge in [(poly.verts.0, poly.verts.1), (poly.verts.1, poly.verts.2), (poly.verts.2, poly.verts.0)] {
+                if map.insert(edge, PolygonId(i as u32)).is_some() {
+                    overused_edges += 1;
+                }
+            }
+        }
+
+        // for each polygon then, by swapping its vertex pairs, you can grab each adjacent polygon
+        // if there is none, the edge is open
+        let mut open_edges = 0;
+        for poly in &mut self.polygons {
+            let [neighbor1, neighbor2, neighbor3] =
+                [(poly.verts.1, poly.verts.0), (poly.verts.2, poly.verts.1), (poly.verts.0, poly.verts.2)].map(|edge| match map.get(&edge) {
+                    Some(&id) => id,
+                    None => {
+                        open_edges += 1;
+                        PolygonId(0)
+                    }
+                });
+            poly.neighbors = (neighbor1, neighbor2, neighbor3);
+        }
+        (open_edges, overused_edges)
+    }
+
+    fn poly_verts(&self, poly: &ShieldPolygon) -> Option<[Vec3d; 3]> {
+        let (a, b, c) = poly.verts;
+        Some([
+            *self.verts.get(a.0 as usize)?,
+            *self.verts.get(b.0 as usize)?,
+            *self.verts.get(c.0 as usize)?,
+        ])
+    }
+
+    // the normal going by the winding order, or None if the polygon has no area to speak of
+    fn winding_normal(&self, poly: &ShieldPolygon) -> Option<Vec3d> {
+        let [v1, v2, v3] = self.poly_verts(poly)?;
+        let cross = (v2 - v1).cross(&(v3 - v1));
+        let longest_edge = [v2 - v1, v3 - v2, v1 - v3]
+            .iter()
+            .map(|edge| edge.magnitude_squared())
+            .fold(0.0, f32::max);
+        (cross.magnitude() > 1e-6 * longest_edge).then(|| cross.normalize())
+    }
+
+    /// Checks the mesh for the problems that make collisions with it unreliable, returning a warning for each kind of problem found.
+    pub fn validate(&self) -> Vec<Warning> {
+        // every undirected edge, with how many polygons use it in each direction
+        let mut edges: HashMap<(VertexId, VertexId), (u32, u32)> = HashMap::new();
+        let (mut degenerate, mut flipped_normals) = (false, false);
+        for poly in &self.polygons {
+            let (a, b, c) = poly.verts;
+            for (from, to) in [(a, b), (b, c), (c, a)] {
+                if from != to {
+                    let uses = edges.entry((from.min(to), from.max(to))).or_default();
+                    if from < to {
+                        uses.0 += 1;
+                    } else {
+                        uses.1 += 1;
+                    }
+                }
+            }
+            match self.winding_normal(poly) {
+                Some(normal) => flipped_normals |= normal.dot(&poly.normal) < 0.0,
+                None => degenerate = true,
+            }
+        }
+
+        let mut warnings = vec![];
+        if edges.values().any(|&(forward, back)| forward + back == 1) {
+            warnings.push(Warning::ShieldOpenEdges);
+        }
+        if edges.values().any(|&(forward, back)| forward + back > 2) {
+            warnings.push(Warning::ShieldNonManifoldEdges);
+        }
+        let inconsistent = edges.values().any(|&uses| uses == (2, 0) || uses == (0, 2));
+        if inconsistent {
+            warnings.push(Warning::ShieldInconsistentWinding);
+        }
+        // the volume only means something if the winding agrees with itself
+        if flipped_normals || (!inconsistent && self.signed_volume(self.polygons.iter().map(|poly| poly.verts)) < 0.0) {
+            warnings.push(Warning::ShieldInwardNormals);
+        }
+        if degenerate {
+            warnings.push(Warning::ShieldDegeneratePolygons);
+        }
+        warnings
+    }
+
+    // the volume enclosed by the triangles, which is negative if they face inwards
+    // measured from the center of the vertices used, so it stays meaningful for meshes that aren't quite closed
+    fn signed_volume(&self, tris: impl Iterator<Item = (VertexId, VertexId, VertexId)> + Clone) -> f32 {
+        let used = tris
+            .clone()
+            .flat_map(|(a, b, c)| [a, b, c])
+            .filter_map(|id| self.verts.get(id.0 as usize).copied());
+        if used.clone().next().is_none() {
+            return 0.0;
+        }
+        let center = Vec3d::average(used);
+        tris.filter_map(|(a, b, c)| {
+            let [v1, v2, v3] = [a, b, c].map(|id| self.verts.get(id.0 as usize).map(|&vert| vert - center));
+            Some(v1?.dot(&v2?.cross(&v3?)) / 6.0)
+        })
+        .sum()
+    }
+
+    /// Fixes what can be fixed automatically: welds together vertices closer than `weld_distance`, drops degenerate polygons,
+    /// makes the winding of each connected piece consistent and outward facing, and recalculates the normals, neighbors and collision tree.
+    ///
+    /// Returns the warnings that remain afterwards; open and non-manifold edges are left to be fixed by hand.
+    pub fn repair(&mut self, weld_distance: f32) -> Vec<Warning> {
+        let (welded, remap) = geometry::weld(&self.verts, weld_distance);
+        let tris = (self.polygons.iter())
+            .filter_map(|poly| {
+                let (a, b, c) = poly.verts;
+                Some([*remap.get(a.0 as usize)?, *remap.get(b.0 as usize)?, *remap.get(c.0 as usize)?])
+            })
+            .collect::<Vec<_>>();
+        let (verts, tris) = geometry::compact(&welded, &tris);
+        self.verts = verts;
+        let polygons = tris
+            .into_iter()
+            .map(|[a, b, c]| ShieldPolygon {
+                normal: Vec3d::ZERO,
+                verts: (VertexId(a), VertexId(b), VertexId(c)),
+                neighbors: Default::default(),
+            })
+            .filter(|poly| self.winding_normal(poly).is_some())
+            .collect();
+        self.polygons = polygons;
+
+        // flood fill across the edges shared by exactly two polygons, flipping polygons to agree with the one they were reached from
+        let mut edge_polys: HashMap<(VertexId, VertexId), Vec<usize>> = HashMap::new();
+        for (i, poly) in self.polygons.iter().enumerate() {
+            let (a, b, c) = poly.verts;
+            for (from, to) in [(a, b), (b, c), (c, a)] {
+                edge_polys.entry((from.min(to), from.max(to))).or_default().push(i);
+            }
+        }
+        let has_edge = |(a, b, c): (VertexId, VertexId, VertexId), from: VertexId, to: VertexId| [(a, b), (b, c), (c, a)].contains(&(from, to));
+        let mut flipped = vec![None; self.polygons.len()];
+        for start in 0..self.polygons.len() {
+            if flipped[start].is_some() {
+                continue;
+            }
+            flipped[start] = Some(false);
+            let mut piece = vec![start];
+            let mut i = 0;
+            while i < piece.len() {
+                let poly = piece[i];
+                let (a, b, c) = self.polygons[poly].verts;
+                let winding = if flipped[poly] == Some(true) { (a, c, b) } else { (a, b, c) };
+                for (from, to) in [(winding.0, winding.1), (winding.1, winding.2), (winding.2, winding.0)] {
+                    if let [first, second] = edge_polys[&(from.min(to), from.max(to))][..] {
+                        let other = if first == poly { second } else { first };
+                        if flipped[other].is_none() {
+                            // the neighbor has to run along the shared edge the other way
+                            flipped[other] = Some(has_edge(self.polygons[other].verts, from, to));
+                            piece.push(other);
+                        }
+                    }
+                }
+                i += 1;
+            }
+
+            let winding = |poly: usize| {
+                let (a, b, c) = self.polygons[poly].verts;
+                if flipped[poly] == Some(true) {
+                    (a, c, b)
+                } else {
+                    (a, b, c)
+                }
+            };
+            if self.signed_volume(piece.iter().map(|&poly| winding(poly))) < 0.0 {
+                for &poly in &piece {
+                    flipped[poly] = flipped[poly].map(|flip| !flip);
+                }
+            }
+        }
+        for (poly, flip) in self.polygons.iter_mut().zip(flipped) {
+            if flip == Some(true) {
+                poly.verts = (poly.verts.0, poly.verts.2, poly.verts.1);
+            }
+        }
+
+        for i in 0..self.polygons.len() {
+            self.polygons[i].normal = self.winding_normal(&self.polygons[i]).unwrap_or_default();
+        }
+        self.recalculate_neighbors();
+        self.collision_tree = Some(ShieldData::recalculate_tree(&self.verts, &self.polygons));
+        self.validate()
+    }
+
     pub fn recalculate_tree(verts: &[Vec3d], polygons: &[ShieldPolygon]) -> ShieldNode {
+        Self::recalculate_tree_with_strategy(verts, polygons, BspStrategy::Median)
+    }
+
+    pub fn recalculate_tree_with_strategy(verts: &[Vec3d], polygons: &[ShieldPolygon], strategy: BspStrategy) -> ShieldNode {
         // these structs make up the smallest bits of data we'll need for this
         // the regular data structure isn't well-optimized for this, so its easier to make something purpose built
         struct ShieldPolyInfo {
@@ -952,20 +1373,17 @@ impl ShieldData {
             })
             .collect::<Vec<_>>();
 
-        fn recalc_recurse(poly_infos: &mut [&ShieldPolyInfo]) -> ShieldNode {
+        fn recalc_recurse(poly_infos: &mut [&ShieldPolyInfo], strategy: BspStrategy) -> ShieldNode {
             if let [poly_info] = *poly_infos {
                 // if theres only one polygon we're at the base case
                 ShieldNode::Leaf { bbox: poly_info.bbox, poly_list: vec![poly_info.id] }
             } else {
                 let bbox = BoundingBox::from_bboxes(poly_infos.iter().map(|poly_info| &poly_info.bbox)).pad(0.01);
-                let axis = bbox.greatest_dimension();
-                poly_infos.sort_by(|a, b| a.center[axis].partial_cmp(&b.center[axis]).unwrap());
-
-                let halfpoint = poly_infos.len() / 2;
+                let split_point = strategy.split(poly_infos, &bbox, |poly_info| poly_info.center, |poly_info| &poly_info.bbox);
 
                 ShieldNode::Split {
-                    front: Box::new(recalc_recurse(&mut poly_infos[..halfpoint])),
-                    back: Box::new(recalc_recurse(&mut poly_infos[halfpoint..])),
+                    front: Box::new(recalc_recurse(&mut poly_infos[..split_point], strategy)),
+                    back: Box::new(recalc_recurse(&mut poly_infos[split_point..], strategy)),
                     bbox,
                 }
             }
@@ -974,7 +1392,7 @@ impl ShieldData {
         if poly_infos.is_empty() {
             ShieldNode::Leaf { bbox: BoundingBox::default(), poly_list: vec![] }
         } else {
-            recalc_recurse(&mut poly_infos.iter().collect::<Vec<_>>())
+            recalc_recurse(&mut poly_infos.iter().collect::<Vec<_>>(), strategy)
         }
     }
 
@@ -1037,6 +1455,84 @@ pub struct Polygon {
     pub verts: Vec<PolyVertex>,
 }
 
+/// How polygons are divided up when building a BSP tree or shield collision tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BspStrategy {
+    /// Split at the median polygon along the greatest dimension; fast, but the halves can overlap a lot on long or oddly shaped models
+    #[default]
+    Median,
+    /// Split where the surface areas of the two halves' bounding boxes, weighted by their polygon counts, are smallest,
+    /// which approximates the cost of testing a ray against the tree
+    SurfaceArea,
+}
+impl BspStrategy {
+    // sorts the items along the axis to split on, and returns the index to split them at, which always leaves both sides non-empty
+    fn split<T>(self, items: &mut [T], bbox: &BoundingBox, center: impl Fn(&T) -> Vec3d, item_bbox: impl Fn(&T) -> &BoundingBox) -> usize {
+        let sort = |items: &mut [T], axis: Axis| items.sort_by(|a, b| center(a)[axis].partial_cmp(&center(b)[axis]).unwrap());
+        match self {
+            BspStrategy::Median => {
+                sort(items, bbox.greatest_dimension());
+                items.len() / 2
+            }
+            BspStrategy::SurfaceArea => {
+                let mut best: Option<(f32, Axis, usize)> = None;
+                let mut back_areas = vec![0.; items.len()];
+                for axis in ALL_AXES {
+                    sort(items, axis);
+
+                    // sweep from the back to get the area of everything from each index onwards...
+                    let mut back_bbox = BoundingBox::EMPTY;
+                    for i in (1..items.len()).rev() {
+                        back_bbox.expand_bbox(item_bbox(&items[i]));
+                        back_areas[i] = back_bbox.surface_area();
+                    }
+
+                    // ...and then from the front, pricing each split as we go
+                    let mut front_bbox = BoundingBox::EMPTY;
+                    for i in 1..items.len() {
+                        front_bbox.expand_bbox(item_bbox(&items[i - 1]));
+                        let cost = front_bbox.surface_area() * i as f32 + back_areas[i] * (items.len() - i) as f32;
+                        if best.map_or(true, |(best_cost, ..)| cost < best_cost) {
+                            best = Some((cost, axis, i));
+                        }
+                    }
+                }
+
+                let (_, axis, split_point) = best.unwrap();
+                if !matches!(axis, Axis::Z) {
+                    sort(items, axis);
+                }
+                split_point
+            }
+        }
+    }
+}
+
+/// How well a BSP tree or shield collision tree is put together, for comparing the trees built by different [`BspStrategy`]s
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TreeQuality {
+    /// Depth of the deepest leaf
+    pub max_depth: u32,
+    /// Average depth of the leaves
+    pub average_depth: f32,
+    pub leaf_count: u32,
+    /// Combined volume of the bounding boxes of every node
+    pub total_bbox_volume: f32,
+    /// `total_bbox_volume` divided by the volume of the root's bounding box, so models of different sizes can be compared; lower is better
+    pub relative_bbox_volume: f32,
+}
+impl TreeQuality {
+    fn new((sum_depth, leaf_count): (u32, u32), max_depth: u32, total_bbox_volume: f32, root_volume: f32) -> Self {
+        TreeQuality {
+            max_depth,
+            average_depth: if leaf_count == 0 { 0. } else { sum_depth as f32 / leaf_count as f32 },
+            leaf_count,
+            total_bbox_volume,
+            relative_bbox_volume: if root_volume > 0. { total_bbox_volume / root_volume } else { 0. },
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum BspNode {
     Split {
@@ -1095,6 +1591,17 @@ impl BspNode {
         }
     }
 
+    pub fn max_depth(&self) -> u32 {
+        match self {
+            BspNode::Split { front, back, .. } => 1 + front.max_depth().max(back.max_depth()),
+            BspNode::Leaf { .. } | BspNode::Empty => 0,
+        }
+    }
+
+    pub fn quality(&self) -> TreeQuality {
+        TreeQuality::new(self.sum_depth_and_size(), self.max_depth(), self.sum_of_bboxes(), self.bbox().volume())
+    }
+
     pub fn recalculate_bboxes(&mut self, verts: &[Vec3d]) {
         match self {
             BspNode::Split { bbox, front, back, .. } => {
@@ -1180,6 +1687,30 @@ impl Iterator for BspNodeIntoIter {
     }
 }
 
+// a point on a polygon, found by BspData::raycast or BspData::closest_point
+pub(crate) struct PolygonHit<'a> {
+    poly: &'a Polygon,
+    point: Vec3d,
+    // the normal of the triangle of the polygon's fan the point is on
+    normal: Vec3d,
+    uv: (f32, f32),
+}
+impl<'a> PolygonHit<'a> {
+    // `u` and `v` weigh the second and third corners of the `i`th triangle of the polygon's fan
+    fn new(poly: &'a Polygon, verts: &[Vec3d], i: usize, u: f32, v: f32) -> Self {
+        let corners = [&poly.verts[0], &poly.verts[i], &poly.verts[i + 1]];
+        let [a, b, c] = corners.map(|vert| verts[vert.vertex_id.0 as usize]);
+        let [uv0, uv1, uv2] = corners.map(|vert| vert.uv);
+        let w = 1.0 - u - v;
+        PolygonHit {
+            poly,
+            point: a * w + b * u + c * v,
+            normal: (b - a).cross(&(c - a)).normalize(),
+            uv: (uv0.0 * w + uv1.0 * u + uv2.0 * v, uv0.1 * w + uv1.1 * u + uv2.1 * v),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct BspData {
     pub verts: Vec<Vec3d>,
@@ -1197,7 +1728,201 @@ impl BspData {
     pub(crate) const SORTNORM2: u32 = 7;
 }
 impl BspData {
+    // builds a tree from polygons indexing into larger vertex and normal lists, giving it compact lists of only what the polygons use
+    // UVs and textures are per polygon so they come along as is
+    pub(crate) fn from_polygons(verts: &[Vec3d], norms: &[Vec3d], mut polygons: Vec<Polygon>) -> BspData {
+        let (mut new_verts, mut new_norms) = (vec![], vec![]);
+        let (mut vert_map, mut norm_map) = (HashMap::new(), HashMap::new());
+        for vert in polygons.iter_mut().flat_map(|poly| &mut poly.verts) {
+            vert.vertex_id = *vert_map.entry(vert.vertex_id).or_insert_with(|| {
+                new_verts.push(verts[vert.vertex_id.0 as usize]);
+                VertexId(new_verts.len() as u32 - 1)
+            });
+            vert.normal_id = *norm_map.entry(vert.normal_id).or_insert_with(|| {
+                new_norms.push(norms[vert.normal_id.0 as usize]);
+                NormalId(new_norms.len() as u32 - 1)
+            });
+        }
+        BspData {
+            collision_tree: BspData::recalculate(&new_verts, polygons.into_iter()),
+            verts: new_verts,
+            norms: new_norms,
+        }
+    }
+
+    // the first polygon the ray from `origin` along the normalized `dir` hits within `max_distance`, treating polygons as
+    // triangle fans, along with the distance
+    pub(crate) fn raycast(&self, origin: Vec3d, dir: Vec3d, max_distance: f32, cull_backfaces: bool) -> Option<(f32, PolygonHit<'_>)> {
+        struct Ray {
+            origin: Vec3d,
+            dir: Vec3d,
+            inv_dir: Vec3d,
+            cull_backfaces: bool,
+        }
+
+        fn visit<'a>(node: &'a BspNode, verts: &[Vec3d], ray: &Ray, best: &mut (f32, Option<PolygonHit<'a>>)) {
+            let entry = |node: &BspNode| geometry::ray_box(ray.origin, ray.inv_dir, node.bbox()).filter(|&t| t <= best.0);
+            match node {
+                BspNode::Split { front, back, .. } => {
+                    // the nearer side first, so the further one can hopefully be skipped
+                    let mut sides = [(entry(front), front), (entry(back), back)];
+                    sides.sort_by(|a, b| a.0.unwrap_or(f32::INFINITY).total_cmp(&b.0.unwrap_or(f32::INFINITY)));
+                    for (side_entry, side) in sides {
+                        if side_entry.is_some() {
+                            visit(side, verts, ray, best);
+                        }
+                    }
+                }
+                BspNode::Leaf { poly, .. } => {
+                    if entry(node).is_none() {
+                        return;
+                    }
+                    for i in 1..poly.verts.len().saturating_sub(1) {
+                        let tri = [0, i, i + 1].map(|corner| verts[poly.verts[corner].vertex_id.0 as usize]);
+                        let Some((t, u, v)) = geometry::ray_triangle(ray.origin, ray.dir, tri) else {
+                            continue;
+                        };
+                        let hit = PolygonHit::new(poly, verts, i, u, v);
+                        if t <= best.0 && !(ray.cull_backfaces && hit.normal.dot(&ray.dir) > 0.0) {
+                            *best = (t, Some(hit));
+                        }
+                    }
+                }
+                BspNode::Empty => {}
+            }
+        }
+
+        let ray = Ray {
+            origin,
+            dir,
+            inv_dir: Vec3d::new(1.0 / dir.x, 1.0 / dir.y, 1.0 / dir.z),
+            cull_backfaces,
+        };
+        let mut best = (max_distance, None);
+        if geometry::ray_box(origin, ray.inv_dir, self.collision_tree.bbox()).is_some_and(|t| t <= max_distance) {
+            visit(&self.collision_tree, &self.verts, &ray, &mut best);
+        }
+        let (distance, hit) = best;
+        Some((distance, hit?))
+    }
+
+    // the point on the polygons nearest to `point` within `max_distance`, treating polygons as triangle fans, along with the distance
+    // culling backfaces ignores polygons `point` is behind
+    pub(crate) fn closest_point(&self, point: Vec3d, max_distance: f32, cull_backfaces: bool) -> Option<(f32, PolygonHit<'_>)> {
+        fn visit<'a>(node: &'a BspNode, verts: &[Vec3d], point: Vec3d, cull_backfaces: bool, best: &mut (f32, Option<PolygonHit<'a>>)) {
+            let distance = |node: &BspNode| Some(geometry::box_distance(point, node.bbox())).filter(|&distance| distance <= best.0);
+            match node {
+                BspNode::Split { front, back, .. } => {
+                    // the nearer side first, so the further one can hopefully be skipped
+                    let mut sides = [(distance(front), front), (distance(back), back)];
+                    sides.sort_by(|a, b| a.0.unwrap_or(f32::INFINITY).total_cmp(&b.0.unwrap_or(f32::INFINITY)));
+                    for (side_distance, side) in sides {
+                        if side_distance.is_some() {
+                            visit(side, verts, point, cull_backfaces, best);
+                        }
+                    }
+                }
+                BspNode::Leaf { poly, .. } => {
+                    if distance(node).is_none() {
+                        return;
+                    }
+                    for i in 1..poly.verts.len().saturating_sub(1) {
+                        let tri = [0, i, i + 1].map(|corner| verts[poly.verts[corner].vertex_id.0 as usize]);
+                        let (u, v) = geometry::closest_point_on_triangle(point, tri);
+                        let hit = PolygonHit::new(poly, verts, i, u, v);
+                        // a triangle with no area has no normal, but whatever is next to it covers it anyway
+                        if hit.normal.x.is_nan() {
+                            continue;
+                        }
+                        let distance = (point - hit.point).magnitude();
+                        if distance <= best.0 && !(cull_backfaces && hit.normal.dot(&(point - hit.point)) < 0.0) {
+                            *best = (distance, Some(hit));
+                        }
+                    }
+                }
+                BspNode::Empty => {}
+            }
+        }
+
+        let mut best = (max_distance, None);
+        visit(&self.collision_tree, &self.verts, point, cull_backfaces, &mut best);
+        let (distance, hit) = best;
+        Some((distance, hit?))
+    }
+
+    // whether every edge is matched by one running the other way, with vertices in the same spot counting as the same one
+    pub(crate) fn is_closed(&self) -> bool {
+        let mut first_at: HashMap<[u32; 3], u32> = HashMap::new();
+        let welded = (self.verts.iter().enumerate())
+            .map(|(i, vert)| *first_at.entry([vert.x.to_bits(), vert.y.to_bits(), vert.z.to_bits()]).or_insert(i as u32))
+            .collect::<Vec<_>>();
+
+        let mut edges: HashMap<(u32, u32), i32> = HashMap::new();
+        for (_, poly) in self.collision_tree.leaves() {
+            let ids = poly.verts.iter().map(|vert| welded[vert.vertex_id.0 as usize]).collect::<Vec<_>>();
+            for (&a, &b) in ids.iter().circular_tuple_windows() {
+                if a != b {
+                    *edges.entry((a.min(b), a.max(b))).or_default() += if a < b { 1 } else { -1 };
+                }
+            }
+        }
+        edges.values().all(|&count| count == 0)
+    }
+
+    // how far along the ray from `origin` along `dir` each polygon it crosses within `max_t` is, in units of `dir`, in order
+    pub(crate) fn crossings(&self, origin: Vec3d, dir: Vec3d, max_t: f32) -> Vec<f32> {
+        // along with whether the polygon faces along the ray
+        fn visit(node: &BspNode, verts: &[Vec3d], origin: Vec3d, dir: Vec3d, inv_dir: Vec3d, max_t: f32, out: &mut Vec<(f32, bool)>) {
+            if !geometry::ray_box(origin, inv_dir, node.bbox()).is_some_and(|t| t <= max_t) {
+                return;
+            }
+            match node {
+                BspNode::Split { front, back, .. } => {
+                    visit(front, verts, origin, dir, inv_dir, max_t, out);
+                    visit(back, verts, origin, dir, inv_dir, max_t, out);
+                }
+                BspNode::Leaf { poly, .. } => {
+                    for i in 1..poly.verts.len().saturating_sub(1) {
+                        let tri = [0, i, i + 1].map(|corner| verts[poly.verts[corner].vertex_id.0 as usize]);
+                        let facing = (tri[1] - tri[0]).cross(&(tri[2] - tri[0])).dot(&dir) > 0.0;
+                        out.extend(
+                            geometry::ray_triangle(origin, dir, tri)
+                                .filter(|&(t, _, _)| t <= max_t)
+                                .map(|(t, _, _)| (t, facing)),
+                        );
+                    }
+                }
+                BspNode::Empty => {}
+            }
+        }
+
+        let mut out = vec![];
+        let inv_dir = Vec3d::new(1.0 / dir.x, 1.0 / dir.y, 1.0 / dir.z);
+        visit(&self.collision_tree, &self.verts, origin, dir, inv_dir, max_t, &mut out);
+        out.sort_by(|a, b| a.0.total_cmp(&b.0));
+        // going through an edge between triangles facing the same way is a single crossing,
+        // whereas grazing one between triangles facing opposite ways crosses twice or not at all
+        out.dedup_by(|a, b| a.0 - b.0 <= 1e-5 && a.1 == b.1);
+        out.into_iter().map(|(t, _)| t).collect()
+    }
+
+    // whether `point` is inside, going by how many polygons rays from it cross, which only means anything for closed meshes
+    pub(crate) fn contains(&self, point: Vec3d) -> bool {
+        // a ray through an edge counts both polygons, so a few rays in odd directions take a vote
+        let dirs = [
+            Vec3d::new(0.57, 0.31, 0.76),
+            Vec3d::new(-0.43, 0.82, -0.37),
+            Vec3d::new(0.29, -0.52, -0.8),
+        ];
+        let inside = dirs.iter().filter(|&&dir| self.crossings(point, dir, f32::INFINITY).len() % 2 == 1);
+        inside.count() >= 2
+    }
+
     pub fn recalculate(verts: &[Vec3d], polygons: impl Iterator<Item = Polygon>) -> BspNode {
+        Self::recalculate_with_strategy(verts, polygons, BspStrategy::Median)
+    }
+
+    pub fn recalculate_with_strategy(verts: &[Vec3d], polygons: impl Iterator<Item = Polygon>, strategy: BspStrategy) -> BspNode {
         // first go over the polygons, filling some data, and exporting their bboxes and centers, which is important for the actual BSP generation
         let polygons = polygons
             .map(|mut poly| {
@@ -1228,20 +1953,17 @@ impl BspData {
             })
             .collect::<Vec<_>>();
 
-        fn recalc_recurse(polygons: &mut [&(Vec3d, BoundingBox, Polygon)]) -> BspNode {
+        fn recalc_recurse(polygons: &mut [&(Vec3d, BoundingBox, Polygon)], strategy: BspStrategy) -> BspNode {
             if let [&(_, bbox, ref polygon)] = *polygons {
                 // if there's only one polygon we're at the base case
                 BspNode::Leaf { bbox, poly: polygon.clone() }
             } else {
                 let bbox = BoundingBox::from_bboxes(polygons.iter().map(|(_, bbox, _)| bbox)).pad(0.01);
-                let axis = bbox.greatest_dimension();
-                polygons.sort_by(|a, b| a.0[axis].partial_cmp(&b.0[axis]).unwrap());
-
-                let halfpoint = polygons.len() / 2;
+                let split_point = strategy.split(polygons, &bbox, |(center, ..)| *center, |(_, bbox, _)| bbox);
 
                 BspNode::Split {
-                    front: Box::new(recalc_recurse(&mut polygons[..halfpoint])),
-                    back: Box::new(recalc_recurse(&mut polygons[halfpoint..])),
+                    front: Box::new(recalc_recurse(&mut polygons[..split_point], strategy)),
+                    back: Box::new(recalc_recurse(&mut polygons[split_point..], strategy)),
                     bbox,
                 }
             }
@@ -1250,7 +1972,7 @@ impl BspData {
         if polygons.is_empty() {
             BspNode::Empty
         } else {
-            recalc_recurse(&mut polygons.iter().collect::<Vec<_>>())
+            recalc_recurse(&mut polygons.iter().collect::<Vec<_>>(), strategy)
         }
     }
 }
@@ -1499,6 +2221,17 @@ impl SubObject {
         parse_uvec_fvec(&self.properties)
     }
 
+    /// The direction this subobject rotates around, if it does; for [`SubsysRotationAxis::Other`] that's its `$uvec`
+    pub fn rotation_axis_vector(&self) -> Option<Vec3d> {
+        match self.rotation_axis {
+            SubsysRotationAxis::None => None,
+            SubsysRotationAxis::X => Some(Vec3d::new(1.0, 0.0, 0.0)),
+            SubsysRotationAxis::Y => Some(Vec3d::new(0.0, 1.0, 0.0)),
+            SubsysRotationAxis::Z => Some(Vec3d::new(0.0, 0.0, 1.0)),
+            SubsysRotationAxis::Other => self.uvec_fvec().map(|(uvec, _)| uvec.normalize()),
+        }
+    }
+
     pub fn is_subsystem(&self) -> bool {
         properties_get_field(&self.properties, "$special") == Some("subsystem")
     }
@@ -1520,30 +2253,265 @@ impl SubObject {
         }
         (surface_area, weighted_sum / surface_area)
     }
-}
 
-fn parse_uvec_fvec(props: &str) -> Option<(Vec3d, Vec3d)> {
-    let uvec = Vec3d::from_str(properties_get_field(props, "$uvec")?).ok()?;
-    let fvec = Vec3d::from_str(properties_get_field(props, "$fvec")?).ok()?;
-    Some((uvec, fvec))
-}
+    /// Checks the polygons for shapes FSO can't render properly, returning a warning for each kind of problem found:
+    /// corners which don't lie on one plane, corners which turn the wrong way, and polygons with no area to speak of.
+    pub fn validate_polygons(&self) -> Vec<Warning> {
+        let (mut zero_area, mut non_planar, mut concave) = (false, false, false);
+        for (_, poly) in self.bsp_data.collision_tree.leaves() {
+            let flaws = PolygonFlaws::of(&self.bsp_data.verts, poly);
+            zero_area |= flaws.zero_area;
+            non_planar |= flaws.non_planar;
+            concave |= flaws.concave;
+        }
 
-impl Serialize for SubObject {
-    fn write_to(&self, w: &mut impl Write) -> io::Result<()> {
-        let version = get_version();
-        self.obj_id.write_to(w)?;
-        if version >= Version::V21_16 {
-            self.radius.write_to(w)?;
-            self.parent.unwrap_or(ObjectId(u32::MAX)).write_to(w)?;
-            self.offset.write_to(w)?;
-        } else {
-            self.parent.unwrap_or(ObjectId(u32::MAX)).write_to(w)?;
-            self.offset.write_to(w)?;
-            self.radius.write_to(w)?;
+        let mut warnings = vec![];
+        if zero_area {
+            warnings.push(Warning::ZeroAreaPolygons(self.obj_id));
         }
-        self.geo_center.write_to(w)?;
-        self.bbox.write_to(w)?;
-        self.name.write_to(w)?;
+        if non_planar {
+            warnings.push(Warning::NonPlanarPolygons(self.obj_id));
+        }
+        if concave {
+            warnings.push(Warning::ConcavePolygons(self.obj_id));
+        }
+        warnings
+    }
+
+    /// Triangulates the polygons [`SubObject::validate_polygons`] finds fault with by ear clipping, keeping the UVs and normals
+    /// of their corners, and drops the ones with no area. Polygons that cross over themselves are left alone.
+    /// The BSP tree is rebuilt if anything changed.
+    ///
+    /// Returns how many polygons were replaced or dropped.
+    pub fn triangulate_bad_polygons(&mut self) -> usize {
+        let verts = &self.bsp_data.verts;
+        let mut num_fixed = 0;
+        let mut polygons = vec![];
+        for (_, poly) in self.bsp_data.collision_tree.leaves() {
+            let flaws = PolygonFlaws::of(verts, poly);
+            if flaws.zero_area {
+                num_fixed += 1;
+            } else if let Some(tris) = (flaws.non_planar || flaws.concave)
+                .then(|| geometry::triangulate(&[poly.verts.iter().map(|vert| verts[vert.vertex_id.0 as usize]).collect()], flaws.normal))
+                .flatten()
+            {
+                num_fixed += 1;
+                polygons.extend(tris.into_iter().map(|tri| Polygon {
+                    normal: poly.normal,
+                    texture: poly.texture,
+                    verts: tri.iter().map(|&i| poly.verts[i as usize].clone()).collect(),
+                }));
+            } else {
+                polygons.push(poly.clone());
+            }
+        }
+
+        if num_fixed > 0 {
+            self.bsp_data.collision_tree = BspData::recalculate(verts, polygons.into_iter());
+        }
+        num_fixed
+    }
+
+    /// Cleans up the geometry as set by `options`, see [`OptimizeOptions`], and rebuilds the BSP tree to match.
+    /// Vertices and normals no polygon uses anymore are dropped, as are polygons welding collapsed down to a line or a point.
+    ///
+    /// Returns the vertex and normal counts before and after, which is what [`Error::TooManyVerts`] and [`Error::TooManyNorms`] go by.
+    pub fn optimize_geometry(&mut self, options: &OptimizeOptions) -> OptimizeReport {
+        let mut report = OptimizeReport {
+            verts_before: self.bsp_data.verts.len(),
+            norms_before: self.bsp_data.norms.len(),
+            ..Default::default()
+        };
+        let mut polygons = self.bsp_data.collision_tree.leaves().map(|(_, poly)| poly.clone()).collect::<Vec<_>>();
+        let mut verts = self.bsp_data.verts.clone();
+        let mut norms = self.bsp_data.norms.clone();
+
+        if let Some(distance) = options.weld_distance {
+            let (welded, remap) = geometry::weld(&verts, distance);
+            verts = welded;
+            for poly in &mut polygons {
+                for vert in &mut poly.verts {
+                    vert.vertex_id = VertexId(remap[vert.vertex_id.0 as usize]);
+                }
+                // neighboring corners may have been welded together
+                poly.verts.dedup_by_key(|vert| vert.vertex_id);
+                if poly.verts.len() > 1 && poly.verts.first().map(|vert| vert.vertex_id) == poly.verts.last().map(|vert| vert.vertex_id) {
+                    poly.verts.pop();
+                }
+            }
+        }
+
+        if options.normals != NormalMode::Keep {
+            // each polygon's normal, and the angle at each of its corners, which is how much it counts toward the smoothed normals there
+            let faces = (polygons.iter())
+                .map(|poly| {
+                    let points = poly.verts.iter().map(|vert| verts[vert.vertex_id.0 as usize]).collect::<Vec<_>>();
+                    let normal = (0..points.len())
+                        .map(|i| points[i].cross(&points[(i + 1) % points.len()]))
+                        .fold(Vec3d::ZERO, |sum, cross| sum + cross);
+                    let angles = (0..points.len())
+                        .map(|i| {
+                            let to_prev = points[(i + points.len() - 1) % points.len()] - points[i];
+                            let to_next = points[(i + 1) % points.len()] - points[i];
+                            if to_prev.is_null() || to_next.is_null() {
+                                0.0
+                            } else {
+                                to_prev.normalize().dot(&to_next.normalize()).clamp(-1.0, 1.0).acos()
+                            }
+                        })
+                        .collect::<Vec<_>>();
+                    (if normal.is_null() { normal } else { normal.normalize() }, angles)
+                })
+                .collect::<Vec<_>>();
+            let smoothed_together = |a: usize, b: usize| match &options.normals {
+                NormalMode::Keep => unreachable!(),
+                NormalMode::Smooth { crease_angle } => faces[a].0.dot(&faces[b].0) >= crease_angle.cos(),
+                NormalMode::SmoothingGroups(groups) => a == b || groups.get(a).zip(groups.get(b)).is_some_and(|(a, b)| a & b != 0),
+            };
+
+            let mut corners_at = HashMap::<VertexId, Vec<(usize, usize)>>::new();
+            for (i, poly) in polygons.iter().enumerate() {
+                for (j, vert) in poly.verts.iter().enumerate() {
+                    corners_at.entry(vert.vertex_id).or_default().push((i, j));
+                }
+            }
+            norms.clear();
+            let mut norm_ids = HashMap::new();
+            for i in 0..polygons.len() {
+                for j in 0..polygons[i].verts.len() {
+                    let normal = corners_at[&polygons[i].verts[j].vertex_id]
+                        .iter()
+                        .filter(|&&(other, _)| smoothed_together(i, other))
+                        .map(|&(other, corner)| faces[other].0 * faces[other].1[corner])
+                        .fold(Vec3d::ZERO, |sum, normal| sum + normal);
+                    let normal = if normal.is_null() { faces[i].0 } else { normal.normalize() };
+                    polygons[i].verts[j].normal_id = *norm_ids.entry(normal).or_insert_with(|| {
+                        norms.push(normal);
+                        NormalId(norms.len() as u32 - 1)
+                    });
+                }
+            }
+        } else if options.dedup_normals {
+            let mut norm_ids = HashMap::new();
+            let remap = (norms.iter())
+                .enumerate()
+                .map(|(i, &normal)| *norm_ids.entry(normal).or_insert(NormalId(i as u32)))
+                .collect::<Vec<_>>();
+            for vert in polygons.iter_mut().flat_map(|poly| &mut poly.verts) {
+                vert.normal_id = remap[vert.normal_id.0 as usize];
+            }
+        }
+
+        let num_polygons = polygons.len();
+        polygons.retain(|poly| poly.verts.len() >= 3);
+        report.polygons_removed = num_polygons - polygons.len();
+
+        self.bsp_data = BspData::from_polygons(&verts, &norms, polygons);
+        self.bbox = self.recalc_bbox();
+        self.radius = self.recalc_radius();
+        report.verts_after = self.bsp_data.verts.len();
+        report.norms_after = self.bsp_data.norms.len();
+        report
+    }
+}
+
+// what's wrong with the shape of a polygon, as far as FSO rendering it is concerned
+struct PolygonFlaws {
+    // the normal going by the winding order, zero if the polygon has no area
+    normal: Vec3d,
+    zero_area: bool,
+    non_planar: bool,
+    concave: bool,
+}
+impl PolygonFlaws {
+    fn of(verts: &[Vec3d], poly: &Polygon) -> PolygonFlaws {
+        let points = poly.verts.iter().map(|vert| verts[vert.vertex_id.0 as usize]).collect::<Vec<_>>();
+        let n = points.len();
+        let edge = |i: usize| points[(i + 1) % n] - points[i];
+        let longest_edge = (0..n).map(|i| edge(i).magnitude_squared()).fold(0.0, f32::max);
+        let normal = (0..n)
+            .map(|i| points[i].cross(&points[(i + 1) % n]))
+            .fold(Vec3d::ZERO, |sum, cross| sum + cross);
+
+        // same as the shield's degenerate polygons, tiny compared to the polygon itself
+        if n < 3 || normal.magnitude() <= 1e-6 * longest_edge {
+            return PolygonFlaws {
+                normal: Vec3d::ZERO,
+                zero_area: true,
+                non_planar: false,
+                concave: false,
+            };
+        }
+        let normal = normal.normalize();
+        let center = Vec3d::average(points.iter().copied());
+        PolygonFlaws {
+            normal,
+            zero_area: false,
+            non_planar: points
+                .iter()
+                .any(|&point| (point - center).dot(&normal).abs() > 1e-3 * longest_edge.sqrt()),
+            concave: (0..n).any(|i| edge((i + n - 1) % n).cross(&edge(i)).dot(&normal) < -1e-6 * longest_edge),
+        }
+    }
+}
+
+/// How [`SubObject::optimize_geometry`] treats the normals
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum NormalMode {
+    /// Leave them as they are
+    #[default]
+    Keep,
+    /// Recompute them, smoothing across the edges where the polygons meet at less than `crease_angle` (in radians)
+    Smooth { crease_angle: f32 },
+    /// Recompute them, smoothing across the edges where the polygons share a smoothing group.
+    /// One bitmask of groups per polygon, in the order of [`BspNode::leaves`]
+    SmoothingGroups(Vec<u32>),
+}
+
+/// Controls what [`SubObject::optimize_geometry`] does, by default nothing besides dropping unused vertices and normals
+#[derive(Debug, Clone, Default)]
+pub struct OptimizeOptions {
+    /// Weld together vertices no further apart than this
+    pub weld_distance: Option<f32>,
+    /// Whether and how to recompute the normals; recomputed normals which come out the same are always shared
+    pub normals: NormalMode,
+    /// Merge identical normals into one, when they aren't being recomputed
+    pub dedup_normals: bool,
+}
+
+/// The vertex and normal counts before and after [`SubObject::optimize_geometry`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OptimizeReport {
+    pub verts_before: usize,
+    pub verts_after: usize,
+    pub norms_before: usize,
+    pub norms_after: usize,
+    /// polygons dropped for having been welded down to a line or a point
+    pub polygons_removed: usize,
+}
+
+fn parse_uvec_fvec(props: &str) -> Option<(Vec3d, Vec3d)> {
+    let uvec = Vec3d::from_str(properties_get_field(props, "$uvec")?).ok()?;
+    let fvec = Vec3d::from_str(properties_get_field(props, "$fvec")?).ok()?;
+    Some((uvec, fvec))
+}
+
+impl Serialize for SubObject {
+    fn write_to(&self, w: &mut impl Write) -> io::Result<()> {
+        let version = get_version();
+        self.obj_id.write_to(w)?;
+        if version >= Version::V21_16 {
+            self.radius.write_to(w)?;
+            self.parent.unwrap_or(ObjectId(u32::MAX)).write_to(w)?;
+            self.offset.write_to(w)?;
+        } else {
+            self.parent.unwrap_or(ObjectId(u32::MAX)).write_to(w)?;
+            self.offset.write_to(w)?;
+            self.radius.write_to(w)?;
+        }
+        self.geo_center.write_to(w)?;
+        self.bbox.write_to(w)?;
+        self.name.write_to(w)?;
         self.properties.write_to(w)?;
         self.rotation_type.write_to(w)?;
         self.rotation_axis.write_to(w)?;
@@ -1829,7 +2797,180 @@ mk_versions! {
     V23_01(2301, "23.01"),
 }
 
-#[derive(Debug, Default)]
+/// A 4-byte POF chunk identifier, such as `OBJ2` or `TXTR`
+pub type ChunkId = [u8; 4];
+
+pub(crate) fn chunk_id_str(id: &ChunkId) -> String {
+    String::from_utf8_lossy(id).to_string()
+}
+
+/// A chunk whose id the parser didn't recognize, kept verbatim so it survives being saved again
+#[derive(Clone)]
+pub struct UnknownChunk {
+    pub id: ChunkId,
+    pub data: Vec<u8>,
+}
+impl Debug for UnknownChunk {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UnknownChunk")
+            .field("id", &chunk_id_str(&self.id))
+            .field("data", &self.data.len())
+            .finish()
+    }
+}
+impl UnknownChunk {
+    pub fn id_str(&self) -> String {
+        chunk_id_str(&self.id)
+    }
+}
+
+/// One subobject reaching into another, as found by [`Model::check_interpenetration`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interpenetration {
+    pub obj_id: ObjectId,
+    pub other: ObjectId,
+    /// how far the deepest point of the edges of either is inside the other
+    pub depth: f32,
+    /// whether they only meet somewhere along the rotation of `obj_id`
+    pub swept: bool,
+}
+impl From<Interpenetration> for Warning {
+    fn from(Interpenetration { obj_id, other, depth, swept }: Interpenetration) -> Self {
+        Warning::SubObjectInterpenetration { obj_id, other, depth: OrdF32(depth), swept }
+    }
+}
+
+/// What [`Model::raycast`] and the other surface queries test against
+#[derive(Debug, Clone, Default)]
+pub struct RaycastOptions {
+    /// only test this detail level and its children, by index into [`ObjHeader::detail_levels`];
+    /// `None` tests every subobject, debris included
+    pub detail_level: Option<usize>,
+    /// subobjects to leave out along with their children
+    pub exclude: Vec<ObjectId>,
+    /// ignore polygons whose back faces the ray
+    pub cull_backfaces: bool,
+    /// ignore anything further along the ray than this
+    pub max_distance: Option<f32>,
+}
+
+/// Where a ray cast with [`Model::raycast`] hit the model, or the point another surface query found
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hit {
+    pub obj_id: ObjectId,
+    /// the index of the polygon in the subobject's [`BspNode::leaves`] order
+    pub polygon: usize,
+    /// in model space
+    pub point: Vec3d,
+    /// the normal of the polygon as wound, whichever side was hit
+    pub normal: Vec3d,
+    pub uv: (f32, f32),
+    /// how far along the ray `point` is, or how far from the queried point
+    pub distance: f32,
+}
+
+/// How much of a turret's arc the rest of the ship is in the way of, as found by [`Model::turret_coverage`]
+#[derive(Debug, Clone, Default)]
+pub struct TurretCoverage {
+    /// unit directions spread evenly over the hemisphere around the turret normal, in model space
+    pub directions: Vec<Vec3d>,
+    /// for each direction, the fraction of fire points whose shot along it hits the ship
+    pub blocked: Vec<f32>,
+}
+impl TurretCoverage {
+    /// The fraction of the whole hemisphere, over all fire points, that only hits the ship
+    pub fn blocked_fraction(&self) -> f32 {
+        if self.blocked.is_empty() {
+            return 0.0;
+        }
+        self.blocked.iter().sum::<f32>() / self.blocked.len() as f32
+    }
+}
+
+/// Something [`Model::mirror`] can make a mirrored copy of, and [`Model::check_symmetry`] looks for the counterpart of
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MirrorItem {
+    PrimaryBank(usize),
+    SecondaryBank(usize),
+    GlowBank(usize),
+    ThrusterBank(usize),
+    SpecialPoint(usize),
+    Path(usize),
+    /// along with its children and the turrets based on any of them
+    SubObject(ObjectId),
+}
+
+/// How [`Model::mirror`] names the copies it makes, by swapping each occurrence of one side's pattern for the other's
+#[derive(Debug, Clone)]
+pub struct MirrorNaming {
+    pub left: String,
+    pub right: String,
+}
+impl Default for MirrorNaming {
+    fn default() -> Self {
+        Self { left: "-L".to_string(), right: "-R".to_string() }
+    }
+}
+impl MirrorNaming {
+    /// Swaps every occurrence of either pattern in `text` for the other one
+    pub fn swap(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(c) = rest.chars().next() {
+            if !self.left.is_empty() && rest.starts_with(&self.left) {
+                out.push_str(&self.right);
+                rest = &rest[self.left.len()..];
+            } else if !self.right.is_empty() && rest.starts_with(&self.right) {
+                out.push_str(&self.left);
+                rest = &rest[self.right.len()..];
+            } else {
+                out.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+        out
+    }
+
+    /// The name for the mirrored copy of something called `name`: the patterns swapped, or the right one added on if it had neither
+    pub fn mirror_name(&self, name: &str) -> String {
+        let swapped = self.swap(name);
+        if swapped == name {
+            format!("{}{}", name, self.right)
+        } else {
+            swapped
+        }
+    }
+}
+
+/// Something without an exact mirror image across the plane, as found by [`Model::check_symmetry`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SymmetryMismatch {
+    pub item: MirrorItem,
+    /// the item of the same kind that comes closest to being its mirror image, which may be itself
+    pub closest: MirrorItem,
+    /// how far off `closest` is, as the furthest any point of either is from its counterpart
+    pub error: f32,
+}
+
+/// The result of [`Model::mass_properties`]
+#[derive(Debug, Clone, Copy)]
+pub struct MassProperties {
+    /// the enclosed volume, or `None` if the mesh wasn't closed and was treated as a shell
+    pub volume: Option<f32>,
+    pub surface_area: f32,
+    pub center_of_mass: Vec3d,
+    /// the inertia tensor about the centre of mass, per unit of mass
+    pub inertia: Mat3d,
+}
+impl MassProperties {
+    /// The inverted inertia tensor for a given mass, which is what [`ObjHeader::moment_of_inertia`] holds
+    pub fn inverse_moi(&self, mass: f32) -> Option<Mat3d> {
+        let inertia = glm::Mat3x3::from(self.inertia) * mass;
+        Some(inertia.try_inverse()?.into())
+    }
+}
+
+#[derive(Debug, Default, Clone)]
 pub struct Model {
     pub version: Version,
     pub header: ObjHeader,
@@ -1848,6 +2989,8 @@ pub struct Model {
     pub docking_bays: Vec<Dock>,
     pub insignias: Vec<Insignia>,
     pub shield_data: Option<ShieldData>,
+    /// chunks not understood by the parser, in the order they appeared; written back out after all the known chunks
+    pub unknown_chunks: Vec<UnknownChunk>,
 
     pub path_to_file: PathBuf,
     pub untextured_idx: Option<TextureId>,
@@ -1998,6 +3141,23 @@ impl Model {
                     .special_points
                     .get(*idx)
                     .map_or(false, |spec_point| spec_point.properties.len() > MAX_PROPERTIES_LEN),
+                Warning::SubObjectPropertiesInvalid(id) => properties_invalid(&self.sub_objects[*id].properties, PropertiesKind::SubObject),
+                Warning::ThrusterPropertiesInvalid(idx) => self
+                    .thruster_banks
+                    .get(*idx)
+                    .is_some_and(|bank| properties_invalid(&bank.properties, PropertiesKind::ThrusterBank)),
+                Warning::DockingBayPropertiesInvalid(idx) => self
+                    .docking_bays
+                    .get(*idx)
+                    .is_some_and(|dock| properties_invalid(&dock.properties, PropertiesKind::DockingBay)),
+                Warning::GlowBankPropertiesInvalid(idx) => self
+                    .glow_banks
+                    .get(*idx)
+                    .is_some_and(|bank| properties_invalid(&bank.properties, PropertiesKind::GlowBank)),
+                Warning::SpecialPointPropertiesInvalid(idx) => self
+                    .special_points
+                    .get(*idx)
+                    .is_some_and(|spec_point| properties_invalid(&spec_point.properties, PropertiesKind::SpecialPoint)),
                 Warning::InvalidDockParentSubmodel(idx) => self.docking_bays.get(*idx).map_or(false, |dock| {
                     properties_get_field(&dock.properties, "$parent_submodel").map_or(false, |name| self.get_obj_id_by_name(name).is_none())
                 }),
@@ -2006,6 +3166,17 @@ impl Model {
                     .detail_levels
                     .get(0)
                     .map_or(false, |id| !self.sub_objects[*id].offset.is_null()),
+                Warning::ZeroAreaPolygons(id) | Warning::NonPlanarPolygons(id) | Warning::ConcavePolygons(id) => {
+                    self.sub_objects[*id].validate_polygons().contains(&warning)
+                }
+                Warning::SubObjectInterpenetration { obj_id, other, .. } => {
+                    self.interpenetration(*obj_id, *other).map(Warning::from).as_ref() == Some(&warning)
+                }
+                Warning::ShieldOpenEdges
+                | Warning::ShieldNonManifoldEdges
+                | Warning::ShieldInconsistentWinding
+                | Warning::ShieldInwardNormals
+                | Warning::ShieldDegeneratePolygons => self.shield_data.as_ref().is_some_and(|shield| shield.validate().contains(&warning)),
             };
 
             let existing_warning = self.warnings.contains(&warning);
@@ -2015,6 +3186,16 @@ impl Model {
                 self.warnings.insert(warning);
             }
         } else {
+            // the full interpenetration check is too slow to run on every change, so only the overlaps it already found are looked at again
+            let num_subobjects = self.sub_objects.len() as u32;
+            let overlaps = (self.warnings.iter())
+                .filter_map(|warning| match *warning {
+                    Warning::SubObjectInterpenetration { obj_id, other, .. } => Some((obj_id, other)),
+                    _ => None,
+                })
+                .filter(|(obj_id, other)| obj_id.0 < num_subobjects && other.0 < num_subobjects)
+                .collect::<Vec<_>>();
+
             self.warnings.clear();
 
             if self.radius_test_failed(None) {
@@ -2050,9 +3231,15 @@ impl Model {
                     self.warnings.insert(Warning::SubObjectPropertiesTooLong(subobj.obj_id));
                 }
 
+                if properties_invalid(&subobj.properties, PropertiesKind::SubObject) {
+                    self.warnings.insert(Warning::SubObjectPropertiesInvalid(subobj.obj_id));
+                }
+
                 if self.version < Version::V23_01 && subobj.translation_axis != SubsysTranslationAxis::None {
                     self.warnings.insert(Warning::SubObjectTranslationInvalidVersion(subobj.obj_id));
                 }
+
+                self.warnings.extend(subobj.validate_polygons());
             }
 
             for (i, dock) in self.docking_bays.iter().enumerate() {
@@ -2064,6 +3251,10 @@ impl Model {
                     self.warnings.insert(Warning::DockingBayPropertiesTooLong(i));
                 }
 
+                if properties_invalid(&dock.properties, PropertiesKind::DockingBay) {
+                    self.warnings.insert(Warning::DockingBayPropertiesInvalid(i));
+                }
+
                 if properties_get_field(&dock.properties, "$name").unwrap_or_default().len() > MAX_NAME_LEN {
                     self.warnings.insert(Warning::DockingBayNameTooLong(i));
                 }
@@ -2082,6 +3273,10 @@ impl Model {
                     if bank.properties.len() > MAX_PROPERTIES_LEN {
                         self.warnings.insert(Warning::ThrusterPropertiesTooLong(i));
                     }
+
+                    if properties_invalid(&bank.properties, PropertiesKind::ThrusterBank) {
+                        self.warnings.insert(Warning::ThrusterPropertiesInvalid(i));
+                    }
                 }
             }
 
@@ -2116,6 +3311,10 @@ impl Model {
                 if glow_bank.properties.len() > MAX_PROPERTIES_LEN {
                     self.warnings.insert(Warning::GlowBankPropertiesTooLong(i));
                 }
+
+                if properties_invalid(&glow_bank.properties, PropertiesKind::GlowBank) {
+                    self.warnings.insert(Warning::GlowBankPropertiesInvalid(i));
+                }
             }
 
             for (i, special_point) in self.special_points.iter().enumerate() {
@@ -2126,6 +3325,10 @@ impl Model {
                 if special_point.properties.len() > MAX_PROPERTIES_LEN {
                     self.warnings.insert(Warning::SpecialPointPropertiesTooLong(i));
                 }
+
+                if properties_invalid(&special_point.properties, PropertiesKind::SpecialPoint) {
+                    self.warnings.insert(Warning::SpecialPointPropertiesInvalid(i));
+                }
             }
 
             for (i, path) in self.paths.iter().enumerate() {
@@ -2148,6 +3351,15 @@ impl Model {
                 self.warnings.insert(Warning::DuplicateDetailLevel(*duped_id));
             }
 
+            let overlaps = (overlaps.into_iter())
+                .filter_map(|(obj_id, other)| self.interpenetration(obj_id, other).map(Warning::from))
+                .collect::<Vec<_>>();
+            self.warnings.extend(overlaps);
+
+            if let Some(shield) = &self.shield_data {
+                self.warnings.extend(shield.validate());
+            }
+
             if self.untextured_idx.is_some() {
                 self.warnings.insert(Warning::UntexturedPolygons);
             }
@@ -2536,13 +3748,15 @@ impl Model {
     }
 
     pub fn apply_transform(&mut self, matrix: &TMat4<f32>) {
+        let mut transformed = vec![];
         for i in 0..self.sub_objects.len() {
             // only apply to top-level subobjects (no parent), apply_transform() will
             // recursively apply the proper transform to its children
             if self.sub_objects[ObjectId(i as u32)].parent().is_none() {
-                self.apply_subobj_transform(ObjectId(i as u32), &matrix, true);
+                self.transform_subobj_recursive(ObjectId(i as u32), &matrix, true, &mut transformed);
             }
         }
+        self.rebuild_transformed_subobjs(&transformed);
 
         self.header.bbox = self.recalc_bbox();
         self.header.max_radius = self.recalc_radius();
@@ -2600,6 +3814,13 @@ impl Model {
     }
 
     pub fn apply_subobj_transform(&mut self, id: ObjectId, matrix: &TMat4<f32>, transform_offset: bool) {
+        let mut transformed = vec![];
+        self.transform_subobj_recursive(id, matrix, transform_offset, &mut transformed);
+        self.rebuild_transformed_subobjs(&transformed);
+    }
+
+    // moves the vertices of the subobject and its children, collecting their ids so their BSP trees can be rebuilt all at once afterwards
+    fn transform_subobj_recursive(&mut self, id: ObjectId, matrix: &TMat4<f32>, transform_offset: bool, transformed: &mut Vec<ObjectId>) {
         let zero = Vec3d::ZERO.into();
         let translation = matrix.transform_point(&zero) - zero;
         let no_trans_matrix = &matrix.append_translation(&(-translation));
@@ -2623,24 +3844,62 @@ impl Model {
             *norm = (&norm_matrix * *norm).normalize();
         }
 
-        subobj.bsp_data.collision_tree =
-            BspData::recalculate(&subobj.bsp_data.verts, std::mem::take(&mut subobj.bsp_data.collision_tree).into_leaves().map(|(_, poly)| poly));
-
-        subobj.bbox = *subobj.bsp_data.collision_tree.bbox();
+        // a mirroring transform turns the polygons inside out unless their winding is flipped along with it
+        if no_trans_matrix.determinant() < 0.0 {
+            for (_, poly) in subobj.bsp_data.collision_tree.leaves_mut() {
+                poly.verts.reverse();
+            }
+        }
 
         if transform_offset {
             subobj.offset = matrix * subobj.offset;
         }
 
+        transformed.push(id);
         let children = subobj.children.clone();
 
         for child_id in children {
-            self.apply_subobj_transform(child_id, no_trans_matrix, true)
+            self.transform_subobj_recursive(child_id, no_trans_matrix, true, transformed)
         }
     }
 
+    fn rebuild_transformed_subobjs(&mut self, ids: &[ObjectId]) {
+        self.rebuild_bsp_trees(ids);
+        for &id in ids {
+            let subobj = &mut self.sub_objects[id];
+            subobj.bbox = *subobj.bsp_data.collision_tree.bbox();
+        }
+    }
+
+    // rebuilds the BSP trees of the given subobjects from the polygons they already have, after their vertices were moved
+    fn rebuild_bsp_trees(&mut self, ids: &[ObjectId]) {
+        let mut subobjs = self
+            .sub_objects
+            .iter_mut()
+            .filter(|subobj| ids.contains(&subobj.obj_id))
+            .collect::<Vec<_>>();
+        for_each_maybe_parallel(&mut subobjs, |subobj| {
+            let polygons = std::mem::take(&mut subobj.bsp_data.collision_tree).into_leaves().map(|(_, poly)| poly);
+            subobj.bsp_data.collision_tree = BspData::recalculate(&subobj.bsp_data.verts, polygons);
+        });
+    }
+
     // as above, but only affects the mesh itself
     pub fn apply_subobj_transform_mesh(&mut self, id: ObjectId, matrix: &TMat4<f32>) {
+        self.transform_subobj_mesh(id, matrix);
+        self.rebuild_bsp_trees(&[id]);
+    }
+
+    /// Applies [`Model::apply_subobj_transform_mesh`] to every subobject, each with its own matrix, rebuilding the BSP trees all at once
+    pub fn apply_subobj_transforms_mesh(&mut self, matrices: &ObjVec<TMat4<f32>>) {
+        let ids = (0..self.sub_objects.len()).map(|i| ObjectId(i as u32)).collect::<Vec<_>>();
+        for &id in &ids {
+            self.transform_subobj_mesh(id, &matrices[id]);
+        }
+        self.rebuild_bsp_trees(&ids);
+    }
+
+    fn transform_subobj_mesh(&mut self, id: ObjectId, matrix: &TMat4<f32>) {
         let zero = Vec3d::ZERO.into();
         let translation = matrix.transform_point(&zero) - zero;
         let no_trans_matrix = &matrix.append_translation(&(-translation));
@@ -2657,8 +3916,12 @@ impl Model {
             *norm = (&norm_matrix * *norm).normalize();
         }
 
-        subobj.bsp_data.collision_tree =
-            BspData::recalculate(&subobj.bsp_data.verts, std::mem::take(&mut subobj.bsp_data.collision_tree).into_leaves().map(|(_, poly)| poly));
+        // same as above, mirrored polygons need rewinding to keep facing outwards
+        if no_trans_matrix.determinant() < 0.0 {
+            for (_, poly) in subobj.bsp_data.collision_tree.leaves_mut() {
+                poly.verts.reverse();
+            }
+        }
     }
 
     pub fn recalc_subobj_offset(&mut self, id: ObjectId) -> Vec3d {
@@ -2743,11 +4006,11 @@ impl Model {
         bbox
     }
 
-    pub fn recalc_mass(&mut self) -> f32 {
+    pub fn recalc_mass(&self) -> f32 {
         4.65 * (self.header.bbox.volume().powf(2.0 / 3.0))
     }
 
-    pub fn recalc_moi(&mut self) -> Option<Mat3d> {
+    pub fn recalc_moi(&self) -> Option<Mat3d> {
         fn sum_verts_recurse(subobjects: &ObjVec<SubObject>, id: ObjectId) -> usize {
             subobjects[id].bsp_data.verts.len() + subobjects[id].children.iter().map(|id| sum_verts_recurse(subobjects, *id)).sum::<usize>()
         }
@@ -2778,7 +4041,8 @@ impl Model {
 
             let point_mass = self.header.mass as f64 / num_verts as f64;
             new_moi *= point_mass;
-            new_moi = new_moi.try_inverse().unwrap();
+            // a flat model has no sensible inverse
+            new_moi = new_moi.try_inverse()?;
 
             Some(new_moi.cast::<f32>().into())
         } else {
@@ -2786,6 +4050,305 @@ impl Model {
         }
     }
 
+    /// Computes the volume, centre of mass and inertia tensor of detail0 and its children from the signed volumes of the
+    /// tetrahedra their polygons span with the origin, which unlike [`Model::recalc_mass`] and [`Model::recalc_moi`] doesn't
+    /// care how finely any part of the mesh is tessellated.
+    ///
+    /// This needs every subobject to be closed and consistently wound; if any isn't, the surfaces are treated as a thin
+    /// shell of uniform density instead. Overlapping subobjects count twice.
+    pub fn mass_properties(&self) -> Option<MassProperties> {
+        let &detail0 = self.header.detail_levels.first()?;
+        let to_f64 = |vert: Vec3d| Vector3::new(vert.x as f64, vert.y as f64, vert.z as f64);
+
+        let mut closed = true;
+        let mut tris = vec![];
+        for subobj in self.sub_objects.iter().filter(|subobj| self.is_obj_id_ancestor(subobj.obj_id, detail0)) {
+            let offset = to_f64(self.get_total_subobj_offset(subobj.obj_id));
+            let verts = &subobj.bsp_data.verts;
+            for (_, poly) in subobj.bsp_data.collision_tree.leaves() {
+                let corner = |i: usize| to_f64(verts[poly.verts[i].vertex_id.0 as usize]) + offset;
+                for i in 1..poly.verts.len().saturating_sub(1) {
+                    tris.push([corner(0), corner(i), corner(i + 1)]);
+                }
+            }
+            closed &= subobj.bsp_data.is_closed();
+        }
+
+        let (mut volume, mut volume_moment, mut volume_second_moment) = (0.0, Vector3::zeros(), Matrix3::zeros());
+        let (mut area, mut area_moment, mut area_second_moment) = (0.0, Vector3::zeros(), Matrix3::zeros());
+        for [a, b, c] in tris {
+            let sum = a + b + c;
+            let outer = a * a.transpose() + b * b.transpose() + c * c.transpose() + sum * sum.transpose();
+
+            // whatever lies outside the mesh is covered once positively and once negatively, and cancels out
+            let det = a.dot(&b.cross(&c));
+            volume += det / 6.0;
+            volume_moment += sum * (det / 24.0);
+            volume_second_moment += outer * (det / 120.0);
+
+            let tri_area = (b - a).cross(&(c - a)).norm() / 2.0;
+            area += tri_area;
+            area_moment += sum * (tri_area / 3.0);
+            area_second_moment += outer * (tri_area / 12.0);
+        }
+
+        // a mesh wound inside out comes out with everything negated
+        let sign = volume.signum();
+        let solid = closed && volume != 0.0;
+        let (weight, moment, second_moment) = if solid {
+            (volume * sign, volume_moment * sign, volume_second_moment * sign)
+        } else {
+            (area, area_moment, area_second_moment)
+        };
+        if weight <= 0.0 {
+            return None;
+        }
+
+        let center_of_mass = moment / weight;
+        let covariance = second_moment / weight - center_of_mass * center_of_mass.transpose();
+        let inertia = Matrix3::identity() * covariance.trace() - covariance;
+
+        Some(MassProperties {
+            volume: solid.then_some(weight as f32),
+            surface_area: area as f32,
+            center_of_mass: center_of_mass.cast::<f32>().into(),
+            inertia: inertia.cast::<f32>().into(),
+        })
+    }
+
+    /// Tests each subobject against its parent and its siblings, as well as the space it sweeps through over a full turn
+    /// if it rotates. Destroyed versions and live debris are left out, since they are meant to overlap what they replace.
+    ///
+    /// Overlaps are found by the edges of one subobject that go inside the other, so only closed meshes can be reached into.
+    /// This is too slow for [`Model::recheck_warnings`], which only looks again at the overlaps already in the warnings.
+    pub fn check_interpenetration(&self) -> Vec<Interpenetration> {
+        let is_replacement =
+            |subobj: &SubObject| (subobj.name_links.iter()).any(|link| matches!(link, NameLink::DestroyedVersionOf(_) | NameLink::LiveDebrisOf(_)));
+
+        let mut pairs = vec![];
+        for subobj in self.sub_objects.iter().filter(|subobj| !is_replacement(subobj)) {
+            let Some(parent) = subobj.parent else { continue };
+            if !is_replacement(&self.sub_objects[parent]) {
+                pairs.push((subobj.obj_id, parent));
+            }
+            for &sibling in &self.sub_objects[parent].children {
+                if sibling > subobj.obj_id && !is_replacement(&self.sub_objects[sibling]) {
+                    pairs.push((subobj.obj_id, sibling));
+                }
+            }
+        }
+
+        let closed = self.sub_objects.iter().map(|_| OnceLock::new()).collect::<Vec<_>>();
+        map_maybe_parallel(pairs, |(obj_id, other)| {
+            // the parent carries its children along as it rotates, so a sibling's rotation is the only other one that matters
+            let other_sweeps = self.sub_objects[obj_id].parent != Some(other);
+            self.interpenetration_with(obj_id, other, &closed)
+                .or_else(|| other_sweeps.then(|| self.interpenetration_with(other, obj_id, &closed)).flatten())
+        })
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    /// How far `obj_id` and `other` reach into each other where they are, or failing that, over a full turn of `obj_id`
+    /// if it rotates. See [`Model::check_interpenetration`].
+    pub fn interpenetration(&self, obj_id: ObjectId, other: ObjectId) -> Option<Interpenetration> {
+        let closed = self.sub_objects.iter().map(|_| OnceLock::new()).collect::<Vec<_>>();
+        self.interpenetration_with(obj_id, other, &closed)
+    }
+
+    // whether each subobject is closed is only worked out when needed, and only once, since that takes a while
+    fn interpenetration_with(&self, obj_id: ObjectId, other: ObjectId, closed: &[OnceLock<bool>]) -> Option<Interpenetration> {
+        // the number of steps a full turn is checked in
+        const SWEEP_STEPS: usize = 24;
+
+        let (subobj, other_subobj) = (&self.sub_objects[obj_id], &self.sub_objects[other]);
+        let (offset, other_offset) = (self.get_total_subobj_offset(obj_id), self.get_total_subobj_offset(other));
+        let (bbox, other_bbox) = (subobj.bsp_data.collision_tree.bbox(), other_subobj.bsp_data.collision_tree.bbox());
+
+        // where they are and where they could swing round to, before doing anything expensive
+        let overlapping = bbox.shift(offset).intersects(&other_bbox.shift(other_offset));
+        let axis = subobj.rotation_axis_vector();
+        let swept_radius = subobj.bsp_data.verts.iter().map(|vert| vert.magnitude()).fold(0.0, f32::max);
+        let sweep_overlapping = axis.is_some() && geometry::box_distance(offset - other_offset, other_bbox) <= swept_radius;
+        if !overlapping && !sweep_overlapping {
+            return None;
+        }
+
+        let tolerance = 0.001 * subobj.radius.min(other_subobj.radius);
+
+        // how deep the edges of `from`, moved into the space of `into` by `transform`, reach into it
+        // going along each edge, whether it's inside flips wherever it crosses a polygon
+        let reach = |from: &SubObject, into: &SubObject, transform: &dyn Fn(Vec3d) -> Vec3d| {
+            let verts = from.bsp_data.verts.iter().map(|&vert| transform(vert)).collect::<Vec<_>>();
+            let bbox = into.bsp_data.collision_tree.bbox();
+            let edges = (from.bsp_data.collision_tree.leaves())
+                .flat_map(|(_, poly)| poly.verts.iter().map(|vert| vert.vertex_id.0 as usize).circular_tuple_windows::<(_, _)>())
+                .map(|(a, b)| (a.min(b), a.max(b)))
+                .filter(|&(a, b)| {
+                    let dir = verts[b] - verts[a];
+                    let inv_dir = Vec3d::new(1.0 / dir.x, 1.0 / dir.y, 1.0 / dir.z);
+                    a != b && geometry::ray_box(verts[a], inv_dir, bbox).is_some_and(|t| t <= 1.0)
+                })
+                .collect::<BTreeSet<_>>();
+            if edges.is_empty() || !*closed[into.obj_id.0 as usize].get_or_init(|| into.bsp_data.is_closed()) {
+                return 0.0;
+            }
+
+            let mut inside_cache = HashMap::new();
+            let mut depth = 0.0_f32;
+            for (a, b) in edges {
+                let (start, dir) = (verts[a], verts[b] - verts[a]);
+                let mut inside = *inside_cache.entry(a).or_insert_with(|| into.bsp_data.contains(start));
+                let mut last = 0.0;
+                for t in into.bsp_data.crossings(start, dir, 1.0).into_iter().chain([1.0]) {
+                    if inside {
+                        // the ends of the inside stretch are on the surface, unless they're the edge's own ends
+                        let samples = [Some((last + t) / 2.0), (last == 0.0).then_some(0.0), (t == 1.0).then_some(1.0)];
+                        for sample in samples.into_iter().flatten() {
+                            if let Some((distance, _)) = into.bsp_data.closest_point(start + dir * sample, f32::INFINITY, false) {
+                                depth = depth.max(distance);
+                            }
+                        }
+                    }
+                    inside = !inside;
+                    last = t;
+                }
+            }
+            depth
+        };
+        let reach_both = |rotate: &dyn Fn(Vec3d, f32) -> Vec3d, angle: f32| {
+            let depth = reach(subobj, other_subobj, &|vert| rotate(vert, angle) + offset - other_offset);
+            depth.max(reach(other_subobj, subobj, &|vert| rotate(vert + other_offset - offset, -angle)))
+        };
+
+        if overlapping {
+            let depth = reach_both(&|vert, _| vert, 0.0);
+            if depth > tolerance {
+                return Some(Interpenetration { obj_id, other, depth, swept: false });
+            }
+        }
+
+        let axis = axis.filter(|_| sweep_overlapping)?;
+        let rotate = |vert: Vec3d, angle: f32| {
+            let (sin, cos) = angle.sin_cos();
+            vert * cos + axis.cross(&vert) * sin + axis * axis.dot(&vert) * (1.0 - cos)
+        };
+        let depth = (1..SWEEP_STEPS)
+            .map(|step| reach_both(&rotate, step as f32 / SWEEP_STEPS as f32 * std::f32::consts::TAU))
+            .fold(0.0, f32::max);
+        (depth > tolerance).then_some(Interpenetration { obj_id, other, depth, swept: true })
+    }
+
+    /// Finds the first polygon hit by the ray from `origin` along `dir`, both in model space. Subobjects are tested at their
+    /// offsets, and their BSP bounding boxes are used to skip whatever the ray can't reach.
+    pub fn raycast(&self, origin: Vec3d, dir: Vec3d, options: &RaycastOptions) -> Option<Hit> {
+        if dir.is_null() {
+            return None;
+        }
+        let dir = dir.normalize();
+        self.nearest_hit(options, |bsp_data, offset, max_distance| bsp_data.raycast(origin - offset, dir, max_distance, options.cull_backfaces))
+    }
+
+    /// Finds the point on the surface of the model nearest to `point`, in model space, and how far it is.
+    /// Culling backfaces ignores polygons `point` is behind.
+    pub fn closest_surface_point(&self, point: Vec3d, options: &RaycastOptions) -> Option<Hit> {
+        self.nearest_hit(options, |bsp_data, offset, max_distance| bsp_data.closest_point(point - offset, max_distance, options.cull_backfaces))
+    }
+
+    /// Finds where on the surface of the model `point` should go: the nearest point if `along` is `None`, otherwise
+    /// the nearest point the line through `point` along that direction hits, on either side.
+    pub fn snap_to_surface(&self, point: Vec3d, along: Option<Vec3d>, options: &RaycastOptions) -> Option<Hit> {
+        match along {
+            None => self.closest_surface_point(point, options),
+            Some(dir) => [-dir, dir]
+                .into_iter()
+                .filter_map(|dir| self.raycast(point, dir, options))
+                .min_by(|a, b| a.distance.total_cmp(&b.distance)),
+        }
+    }
+
+    /// Samples `samples` directions over the hemisphere around a turret's normal and casts a ray along each from every
+    /// fire point against detail0, leaving out the turret's own subobjects. Turrets without fire points have nothing to check.
+    pub fn turret_coverage(&self, turret_idx: usize, samples: usize) -> Option<TurretCoverage> {
+        let turret = self.turrets.get(turret_idx)?;
+        if turret.normal.0.is_null() || turret.fire_points.is_empty() || samples == 0 {
+            return None;
+        }
+
+        let gun_offset = self.get_total_subobj_offset(turret.gun_obj);
+        let origins: Vec<Vec3d> = turret.fire_points.iter().map(|&point| point + gun_offset).collect();
+
+        // any two axes perpendicular to the normal will do
+        let normal = turret.normal.0.normalize();
+        let helper = if normal.x.abs() < 0.9 {
+            Vec3d::new(1.0, 0.0, 0.0)
+        } else {
+            Vec3d::new(0.0, 1.0, 0.0)
+        };
+        let right = normal.cross(&helper).normalize();
+        let up = normal.cross(&right);
+
+        // a fibonacci spiral, with the height along the normal spaced evenly so each direction covers the same area
+        let golden_angle = std::f32::consts::PI * (3.0 - 5f32.sqrt());
+        let directions: Vec<Vec3d> = (0..samples)
+            .map(|i| {
+                let height = 1.0 - (i as f32 + 0.5) / samples as f32;
+                let radius = (1.0 - height * height).sqrt();
+                let (sin, cos) = (i as f32 * golden_angle).sin_cos();
+                right * (radius * cos) + up * (radius * sin) + normal * height
+            })
+            .collect();
+
+        let options = RaycastOptions {
+            detail_level: Some(0),
+            exclude: vec![turret.base_obj, turret.gun_obj],
+            ..Default::default()
+        };
+        let blocked = map_maybe_parallel(directions.clone(), |dir| {
+            let hits = origins.iter().filter(|&&origin| self.raycast(origin, dir, &options).is_some()).count();
+            hits as f32 / origins.len() as f32
+        });
+
+        Some(TurretCoverage { directions, blocked })
+    }
+
+    // runs a query on the BSP data of each subobject the options allow, at its offset, keeping the nearest hit
+    fn nearest_hit<'a>(&'a self, options: &RaycastOptions, query: impl Fn(&'a BspData, Vec3d, f32) -> Option<(f32, PolygonHit<'a>)>) -> Option<Hit> {
+        let root = match options.detail_level {
+            Some(level) => Some(*self.header.detail_levels.get(level)?),
+            None => None,
+        };
+
+        let mut best: Option<Hit> = None;
+        for subobj in &self.sub_objects {
+            if root.is_some_and(|root| !self.is_obj_id_ancestor(subobj.obj_id, root))
+                || options.exclude.iter().any(|&id| self.is_obj_id_ancestor(subobj.obj_id, id))
+            {
+                continue;
+            }
+
+            let max_distance = best.as_ref().map_or(options.max_distance.unwrap_or(f32::INFINITY), |hit| hit.distance);
+            let offset = self.get_total_subobj_offset(subobj.obj_id);
+            if let Some((distance, hit)) = query(&subobj.bsp_data, offset, max_distance) {
+                best = Some(Hit {
+                    obj_id: subobj.obj_id,
+                    polygon: subobj
+                        .bsp_data
+                        .collision_tree
+                        .leaves()
+                        .position(|(_, leaf)| std::ptr::eq(leaf, hit.poly))
+                        .unwrap(),
+                    point: hit.point + offset,
+                    normal: hit.normal,
+                    uv: hit.uv,
+                    distance,
+                });
+            }
+        }
+        best
+    }
+
     /// returns the surface area of detail0 and its children, and the average surface area position
     pub fn surface_area_average_pos(&self) -> (f32, Vec3d) {
         let mut surface_area = 0.0;
@@ -2860,6 +4423,21 @@ impl Model {
         }
     }
 
+    /// Rebuilds every BSP tree (and the shield's collision tree) from its polygons, and recomputes all bounding boxes and radii to match
+    pub fn recalculate_geometry(&mut self, strategy: BspStrategy) {
+        for_each_maybe_parallel(&mut self.sub_objects.0, |subobj| {
+            let polygons = std::mem::take(&mut subobj.bsp_data.collision_tree).into_leaves().map(|(_, poly)| poly);
+            subobj.bsp_data.collision_tree = BspData::recalculate_with_strategy(&subobj.bsp_data.verts, polygons, strategy);
+            subobj.bbox = subobj.recalc_bbox();
+            subobj.radius = subobj.recalc_radius();
+        });
+        self.header.bbox = self.recalc_bbox();
+        self.header.max_radius = self.recalc_radius();
+        if let Some(shield) = &mut self.shield_data {
+            shield.collision_tree = Some(ShieldData::recalculate_tree_with_strategy(&shield.verts, &shield.polygons, strategy));
+        }
+    }
+
     pub fn clean_up(&mut self) {
         if let Some(shield) = &mut self.shield_data {
             if shield.collision_tree.is_none() {
@@ -2895,13 +4473,833 @@ impl Model {
     }
 
     pub fn max_verts_norms_per_subobj(&self) -> usize {
-        if self.version >= Version::V23_00 {
+        Self::max_verts_norms_for(self.version)
+    }
+
+    /// How many vertices and normals each subobject can have in a POF of the given version
+    pub fn max_verts_norms_for(version: Version) -> usize {
+        if version >= Version::V23_00 {
             u32::MAX as usize
         } else {
             u16::MAX as usize
         }
     }
 
+    /// Splits a subobject with more vertices or normals than the current version allows into spatially coherent pieces which each fit.
+    /// The subobject keeps the first piece, and the rest become its children, sharing its frame of reference so nothing moves.
+    ///
+    /// Returns the ids of the new children, which is empty if the subobject already fit.
+    pub fn split_oversized_subobject(&mut self, id: ObjectId) -> Vec<ObjectId> {
+        fn fits(polygons: &[Polygon], limit: usize) -> bool {
+            let verts = polygons
+                .iter()
+                .flat_map(|poly| &poly.verts)
+                .map(|vert| vert.vertex_id)
+                .collect::<HashSet<_>>();
+            let norms = polygons
+                .iter()
+                .flat_map(|poly| &poly.verts)
+                .map(|vert| vert.normal_id)
+                .collect::<HashSet<_>>();
+            verts.len() <= limit && norms.len() <= limit
+        }
+
+        // same as the BSP generation, split on the greatest dimension at the median until each half fits
+        fn partition(verts: &[Vec3d], mut polygons: Vec<Polygon>, limit: usize, out: &mut Vec<Vec<Polygon>>) {
+            if polygons.len() <= 1 || fits(&polygons, limit) {
+                out.push(polygons);
+                return;
+            }
+
+            let center = |poly: &Polygon| Vec3d::average(poly.verts.iter().map(|vert| verts[vert.vertex_id.0 as usize]));
+            let axis = BoundingBox::from_vectors(polygons.iter().map(center)).greatest_dimension();
+            polygons.sort_by(|a, b| center(a)[axis].partial_cmp(&center(b)[axis]).unwrap());
+
+            let back = polygons.split_off(polygons.len() / 2);
+            partition(verts, polygons, limit, out);
+            partition(verts, back, limit, out);
+        }
+
+        let limit = self.max_verts_norms_per_subobj();
+        let bsp_data = &self.sub_objects[id].bsp_data;
+        if bsp_data.verts.len() <= limit && bsp_data.norms.len() <= limit {
+            return vec![];
+        }
+
+        let polygons = bsp_data.collision_tree.leaves().map(|(_, poly)| poly.clone()).collect();
+        let mut pieces = vec![];
+        partition(&bsp_data.verts, polygons, limit, &mut pieces);
+        let pieces = pieces
+            .into_iter()
+            .map(|polygons| BspData::from_polygons(&bsp_data.verts, &bsp_data.norms, polygons))
+            .collect::<Vec<_>>();
+        let mut pieces = pieces.into_iter();
+
+        let subobj = &mut self.sub_objects[id];
+        subobj.bsp_data = pieces.next().unwrap_or_default();
+        subobj.bbox = subobj.recalc_bbox();
+        subobj.radius = subobj.recalc_radius();
+        let name = subobj.name.clone();
+
+        let mut new_ids = vec![];
+        for bsp_data in pieces {
+            let new_id = ObjectId(self.sub_objects.len() as u32);
+            let name = (1..)
+                .map(|i| format!("{}-split{}", name, new_ids.len() + i))
+                .find(|new_name| !self.sub_objects.iter().any(|subobj| subobj.name == *new_name))
+                .unwrap();
+            let mut new_subobj = SubObject {
+                obj_id: new_id,
+                parent: Some(id),
+                name,
+                bsp_data,
+                ..Default::default()
+            };
+            new_subobj.bbox = new_subobj.recalc_bbox();
+            new_subobj.radius = new_subobj.recalc_radius();
+            self.sub_objects.push(new_subobj);
+            new_ids.push(new_id);
+        }
+
+        self.header.num_subobjects = self.sub_objects.len() as u32;
+        self.recalc_all_children_ids();
+        self.recalc_semantic_name_links();
+        self.recheck_warnings(Set::All);
+        self.recheck_errors(Set::All);
+        new_ids
+    }
+
+    /// Makes a mirrored copy of `item` across the plane through the origin perpendicular to `axis`, after any others of its kind.
+    /// Normals are mirrored along with positions, and a subobject is copied along with its children and the turrets based on them,
+    /// its polygons rewound so they still face outwards.
+    ///
+    /// The copy's name, and the names it refers to in its properties or as its parent, have their sides swapped following `naming`.
+    /// Glow banks are moved onto the mirrored subobject if there's one by that name.
+    ///
+    /// Returns the copy, or `None` if `item` doesn't exist.
+    pub fn mirror(&mut self, item: MirrorItem, axis: Axis, naming: &MirrorNaming) -> Option<MirrorItem> {
+        let matrix = mirror_matrix(axis);
+        let new_item = match item {
+            MirrorItem::PrimaryBank(idx) | MirrorItem::SecondaryBank(idx) => {
+                let banks = if let MirrorItem::PrimaryBank(_) = item {
+                    &mut self.primary_weps
+                } else {
+                    &mut self.secondary_weps
+                };
+                let mut bank = banks.get(idx)?.clone();
+                for point in &mut bank {
+                    point.apply_transform(&matrix);
+                }
+                banks.push(bank);
+                if let MirrorItem::PrimaryBank(_) = item {
+                    MirrorItem::PrimaryBank(banks.len() - 1)
+                } else {
+                    MirrorItem::SecondaryBank(banks.len() - 1)
+                }
+            }
+            MirrorItem::GlowBank(idx) => {
+                let mut bank = self.glow_banks.get(idx)?.clone();
+                for point in &mut bank.glow_points {
+                    point.apply_transform(&matrix);
+                }
+                bank.properties = naming.swap(&bank.properties);
+                if let Some(parent) = self.sub_objects.get(bank.obj_parent.0 as usize) {
+                    bank.obj_parent = self.get_obj_id_by_name(&naming.swap(&parent.name)).unwrap_or(bank.obj_parent);
+                }
+                self.glow_banks.push(bank);
+                MirrorItem::GlowBank(self.glow_banks.len() - 1)
+            }
+            MirrorItem::ThrusterBank(idx) => {
+                let mut bank = self.thruster_banks.get(idx)?.clone();
+                for point in &mut bank.glows {
+                    point.apply_transform(&matrix);
+                }
+                bank.properties = naming.swap(&bank.properties);
+                self.thruster_banks.push(bank);
+                MirrorItem::ThrusterBank(self.thruster_banks.len() - 1)
+            }
+            MirrorItem::SpecialPoint(idx) => {
+                let mut point = self.special_points.get(idx)?.clone();
+                point.apply_transform(&matrix);
+                point.name = naming.mirror_name(&point.name);
+                point.properties = naming.swap(&point.properties);
+                self.special_points.push(point);
+                MirrorItem::SpecialPoint(self.special_points.len() - 1)
+            }
+            MirrorItem::Path(idx) => {
+                let mut path = self.paths.get(idx)?.clone();
+                path.apply_transform(&matrix);
+                path.name = naming.mirror_name(&path.name);
+                path.parent = naming.swap(&path.parent);
+                self.paths.push(path);
+                MirrorItem::Path(self.paths.len() - 1)
+            }
+            MirrorItem::SubObject(id) => {
+                self.sub_objects.get(id.0 as usize)?;
+                let hierarchy = (0..self.sub_objects.len() as u32)
+                    .map(ObjectId)
+                    .filter(|&other| self.is_obj_id_ancestor(other, id))
+                    .collect::<Vec<_>>();
+                let first_id = self.sub_objects.len() as u32;
+                let new_id = |old: ObjectId| ObjectId(first_id + hierarchy.iter().position(|&other| other == old).unwrap() as u32);
+
+                for &old in &hierarchy {
+                    let original = &self.sub_objects[old];
+                    let mirrored_name = naming.mirror_name(&original.name);
+                    let name = (1..)
+                        .map(|i| {
+                            if i == 1 {
+                                mirrored_name.clone()
+                            } else {
+                                format!("{}{}", mirrored_name, i)
+                            }
+                        })
+                        .find(|new_name| !self.sub_objects.iter().any(|subobj| subobj.name == *new_name))
+                        .unwrap();
+                    let mut properties = naming.swap(&original.properties);
+                    if let Some((uvec, fvec)) = original.uvec_fvec() {
+                        properties_update_field(&mut properties, "$uvec", &(&matrix * uvec).to_string());
+                        properties_update_field(&mut properties, "$fvec", &(&matrix * fvec).to_string());
+                    }
+                    let new_subobj = SubObject {
+                        obj_id: new_id(old),
+                        parent: if old == id { original.parent } else { original.parent.map(new_id) },
+                        name,
+                        properties,
+                        geo_center: &matrix * original.geo_center,
+                        children: vec![],
+                        name_links: vec![],
+                        ..original.clone()
+                    };
+                    self.sub_objects.push(new_subobj);
+                }
+
+                // the copy hangs off the same parent, but mirrored in model space; its children are mirrored relative to it
+                let parent_offset = self.sub_objects[id]
+                    .parent
+                    .map_or(Vec3d::ZERO, |parent| self.get_total_subobj_offset(parent));
+                self.sub_objects[new_id(id)].offset = &matrix * self.get_total_subobj_offset(id) - parent_offset;
+                self.header.num_subobjects = self.sub_objects.len() as u32;
+                self.recalc_all_children_ids();
+                self.apply_subobj_transform(new_id(id), &matrix, false);
+
+                let new_turrets = (self.turrets.iter())
+                    .filter(|turret| hierarchy.contains(&turret.base_obj))
+                    .map(|turret| {
+                        let mut new_turret = Turret {
+                            base_obj: new_id(turret.base_obj),
+                            gun_obj: if hierarchy.contains(&turret.gun_obj) {
+                                new_id(turret.gun_obj)
+                            } else {
+                                turret.gun_obj
+                            },
+                            ..turret.clone()
+                        };
+                        new_turret.apply_transform(&matrix);
+                        new_turret
+                    })
+                    .collect::<Vec<_>>();
+                self.turrets.extend(new_turrets);
+
+                self.recalc_semantic_name_links();
+                MirrorItem::SubObject(new_id(id))
+            }
+        };
+
+        self.recheck_warnings(Set::All);
+        self.recheck_errors(Set::All);
+        Some(new_item)
+    }
+
+    /// Looks for the mirror image across the plane through the origin perpendicular to `axis` of every weapon, glow and thruster bank,
+    /// special point, path and subobject, among the others of the same kind; something on the plane can be its own mirror image.
+    /// Banks and paths are compared by their points, and subobjects by their offsets and bounding boxes in model space.
+    ///
+    /// Returns whatever has nothing within `tolerance` of its mirror image, with whatever came closest; a pair that are each other's
+    /// closest is only reported once.
+    pub fn check_symmetry(&self, axis: Axis, tolerance: f32) -> Vec<SymmetryMismatch> {
+        let matrix = mirror_matrix(axis);
+        // the furthest any point of either is from the nearest point of the mirror image of the other
+        let error = |points: &[Vec3d], other: &[Vec3d]| {
+            if points.len() != other.len() {
+                return f32::INFINITY;
+            }
+            let mirrored = other.iter().map(|&point| &matrix * point).collect::<Vec<_>>();
+            let furthest = |from: &[Vec3d], to: &[Vec3d]| {
+                (from.iter())
+                    .map(|&point| to.iter().map(|&other| (point - other).magnitude()).fold(f32::INFINITY, f32::min))
+                    .fold(0.0, f32::max)
+            };
+            furthest(points, &mirrored).max(furthest(&mirrored, points))
+        };
+
+        let subobj_points = |subobj: &SubObject| {
+            let offset = self.get_total_subobj_offset(subobj.obj_id);
+            let mut points = vec![offset];
+            // going by the vertices, since the stored bounding boxes may or may not be padded
+            let bbox = BoundingBox::from_vectors(subobj.bsp_data.verts.iter().copied());
+            if !bbox.is_inverted() {
+                let BoundingBox { min, max } = bbox;
+                points.extend((0..8).map(|i| {
+                    let corner = |bit: u32, min: f32, max: f32| if i & bit != 0 { max } else { min };
+                    offset + Vec3d::new(corner(1, min.x, max.x), corner(2, min.y, max.y), corner(4, min.z, max.z))
+                }));
+            }
+            points
+        };
+
+        // each kind of item, with the points of each one of them
+        type PointSets = (fn(usize) -> MirrorItem, Vec<Vec<Vec3d>>);
+        let kinds: [PointSets; 7] = [
+            (
+                MirrorItem::PrimaryBank,
+                self.primary_weps
+                    .iter()
+                    .map(|bank| bank.iter().map(|point| point.position).collect())
+                    .collect(),
+            ),
+            (
+                MirrorItem::SecondaryBank,
+                self.secondary_weps
+                    .iter()
+                    .map(|bank| bank.iter().map(|point| point.position).collect())
+                    .collect(),
+            ),
+            (
+                MirrorItem::GlowBank,
+                self.glow_banks
+                    .iter()
+                    .map(|bank| bank.glow_points.iter().map(|point| point.position).collect())
+                    .collect(),
+            ),
+            (
+                MirrorItem::ThrusterBank,
+                self.thruster_banks
+                    .iter()
+                    .map(|bank| bank.glows.iter().map(|point| point.position).collect())
+                    .collect(),
+            ),
+            (MirrorItem::SpecialPoint, self.special_points.iter().map(|point| vec![point.position]).collect()),
+            (
+                MirrorItem::Path,
+                self.paths
+                    .iter()
+                    .map(|path| path.points.iter().map(|point| point.position).collect())
+                    .collect(),
+            ),
+            (|idx| MirrorItem::SubObject(ObjectId(idx as u32)), self.sub_objects.iter().map(subobj_points).collect()),
+        ];
+
+        let mut mismatches: Vec<SymmetryMismatch> = vec![];
+        for (item, point_sets) in kinds {
+            for (idx, points) in point_sets.iter().enumerate().filter(|(_, points)| !points.is_empty()) {
+                // there's always at least itself to compare against
+                let (closest, error) = (point_sets.iter().enumerate())
+                    .map(|(other_idx, other)| (other_idx, error(points, other)))
+                    .min_by(|a, b| a.1.total_cmp(&b.1))
+                    .unwrap();
+                let already_reported = mismatches
+                    .iter()
+                    .any(|mismatch| mismatch.item == item(closest) && mismatch.closest == item(idx));
+                if error > tolerance && !already_reported {
+                    mismatches.push(SymmetryMismatch { item: item(idx), closest: item(closest), error });
+                }
+            }
+        }
+        mismatches
+    }
+
+    /// Generates lower detail levels from the detail0 hierarchy, one for each of `ratios`, each a copy of it with every subobject's
+    /// polygons simplified down to that fraction of the triangles. UV seams and texture boundaries are kept exactly where they are.
+    ///
+    /// The new levels come after any existing ones, and are named following the detail0 ones: "detail0" gives "detail1" and so on,
+    /// and the child names ending in 'a' have it swapped for 'b', 'c' and so on (other names get the letter added on,
+    /// but can only be linked up once the original is renamed to end in 'a'). Destroyed versions and live debris aren't copied.
+    /// Levels past 'h' aren't generated.
+    ///
+    /// Returns the ids of the new detail level subobjects, which is empty if the model has no detail0.
+    pub fn generate_lods(&mut self, ratios: &[f32]) -> Vec<ObjectId> {
+        // simplifies the polygons with a vertex for each combination of position, UV and texture, so a seam splits the mesh
+        // with the positions along it pinned in place, and both sides of it are left with the exact same edges along it
+        fn decimate_bsp_data(bsp_data: &BspData, ratio: f32) -> BspData {
+            let mut vert_map = HashMap::new();
+            let (mut positions, mut poly_verts) = (vec![], vec![]);
+            let (mut tris, mut corners, mut textures) = (vec![], vec![], vec![]);
+            for (_, poly) in bsp_data.collision_tree.leaves() {
+                for i in 1..poly.verts.len().saturating_sub(1) {
+                    let corner = [&poly.verts[0], &poly.verts[i], &poly.verts[i + 1]].map(Clone::clone);
+                    tris.push(corner.each_ref().map(|vert| {
+                        let pos = bsp_data.verts[vert.vertex_id.0 as usize];
+                        let key = ([pos.x, pos.y, pos.z].map(f32::to_bits), [vert.uv.0, vert.uv.1].map(f32::to_bits), poly.texture);
+                        *vert_map.entry(key).or_insert_with(|| {
+                            positions.push(pos);
+                            poly_verts.push(vert.clone());
+                            positions.len() as u32 - 1
+                        })
+                    }));
+                    corners.push(corner);
+                    textures.push(poly.texture);
+                }
+            }
+
+            let mut uses_of_position = HashMap::<_, usize>::new();
+            for (pos, _, _) in vert_map.keys() {
+                *uses_of_position.entry(*pos).or_default() += 1;
+            }
+            let pinned = (positions.iter())
+                .map(|pos| uses_of_position[&[pos.x, pos.y, pos.z].map(f32::to_bits)] > 1)
+                .collect::<Vec<_>>();
+
+            let target = (tris.len() as f32 * ratio.clamp(0.0, 1.0)).round() as usize;
+            let polygons = geometry::decimate(&positions, &tris, target, &pinned)
+                .into_iter()
+                .map(|(i, tri)| Polygon {
+                    normal: Vec3d::ZERO,
+                    texture: textures[i],
+                    // corners the triangle started out with keep their normals, the rest take one from their vertex
+                    verts: (tri.iter())
+                        .map(|&vert| match tris[i].iter().position(|&other| other == vert) {
+                            Some(j) => corners[i][j].clone(),
+                            None => poly_verts[vert as usize].clone(),
+                        })
+                        .collect(),
+                })
+                .collect();
+            BspData::from_polygons(&bsp_data.verts, &bsp_data.norms, polygons)
+        }
+
+        fn lod_name(name: &str, level: usize, is_root: bool) -> String {
+            if is_root {
+                format!("{}{}", name.trim_end_matches(|c: char| c.is_ascii_digit()), level)
+            } else {
+                let letter = (b'a' + level as u8) as char;
+                format!("{}{}", name.strip_suffix('a').unwrap_or(name), letter)
+            }
+        }
+
+        let Some(&detail_0) = self.header.detail_levels.first() else {
+            return vec![];
+        };
+
+        self.recalc_semantic_name_links();
+        let is_copied = |model: &Model, id: ObjectId| {
+            model.is_obj_id_ancestor(id, detail_0)
+                && !model.sub_objects.iter().any(|subobj| {
+                    model.is_obj_id_ancestor(id, subobj.obj_id)
+                        && (subobj.name_links.iter()).any(|link| matches!(link, NameLink::DestroyedVersionOf(_) | NameLink::LiveDebrisOf(_)))
+                })
+        };
+        let hierarchy = (0..self.sub_objects.len() as u32)
+            .map(ObjectId)
+            .filter(|&id| is_copied(self, id))
+            .collect::<Vec<_>>();
+
+        let mut new_ids = vec![];
+        for (level, &ratio) in (self.header.detail_levels.len()..=7).zip(ratios) {
+            let decimated = hierarchy.iter().map(|&id| (id, &self.sub_objects[id].bsp_data)).collect::<Vec<_>>();
+            let decimated = map_maybe_parallel(decimated, |(id, bsp_data)| (id, decimate_bsp_data(bsp_data, ratio)));
+
+            let first_id = self.sub_objects.len() as u32;
+            let new_id = |id: ObjectId| ObjectId(first_id + hierarchy.iter().position(|&other| other == id).unwrap() as u32);
+            for (id, bsp_data) in decimated {
+                let original = &self.sub_objects[id];
+                let mut new_subobj = SubObject {
+                    obj_id: new_id(id),
+                    parent: original.parent.map(new_id),
+                    name: lod_name(&original.name, level, id == detail_0),
+                    bsp_data,
+                    children: vec![],
+                    name_links: vec![],
+                    ..original.clone()
+                };
+                new_subobj.bbox = new_subobj.recalc_bbox();
+                new_subobj.radius = new_subobj.recalc_radius();
+                self.sub_objects.push(new_subobj);
+            }
+
+            self.header.detail_levels.push(new_id(detail_0));
+            new_ids.push(new_id(detail_0));
+        }
+
+        self.header.num_subobjects = self.sub_objects.len() as u32;
+        self.recalc_all_children_ids();
+        self.recalc_semantic_name_links();
+        self.recheck_warnings(Set::All);
+        self.recheck_errors(Set::All);
+        new_ids
+    }
+
+    /// Fractures a subobject (only its own polygons, not its children's) into up to `num_pieces` debris subobjects,
+    /// by repeatedly slicing the biggest piece in two, with `seed` picking where the cuts go. The cuts are capped,
+    /// so each piece is as closed as the original was, and the caps use the original's most used texture, projected flat onto them.
+    ///
+    /// The original is left as is, and the pieces stay where they were cut from, each with its own center as its origin.
+    /// No more pieces are made than fit under the debris limit.
+    ///
+    /// Returns the ids of the new debris subobjects.
+    pub fn generate_debris(&mut self, id: ObjectId, num_pieces: usize, seed: u32) -> Vec<ObjectId> {
+        // cuts the polygons in two by the plane, adding the vertices and normals needed along the cut,
+        // and returns the polygons in front of the plane, the ones behind it, and the edges along the cut, wound the way the front's cap needs
+        fn slice(
+            verts: &mut Vec<Vec3d>, norms: &mut Vec<Vec3d>, polygons: Vec<Polygon>, point: Vec3d, normal: Vec3d,
+        ) -> (Vec<Polygon>, Vec<Polygon>, Vec<(VertexId, VertexId)>) {
+            let dists = verts.iter().map(|&vert| (vert - point).dot(&normal)).collect::<Vec<_>>();
+            let in_front = |vert: &PolyVertex| dists[vert.vertex_id.0 as usize] >= 0.0;
+            let (mut cut_verts, mut cut_norms) = (HashMap::new(), HashMap::new());
+            let (mut front, mut back, mut cap_edges) = (vec![], vec![], vec![]);
+            for poly in polygons {
+                if poly.verts.iter().all(in_front) {
+                    front.push(poly);
+                    continue;
+                } else if !poly.verts.iter().any(in_front) {
+                    back.push(poly);
+                    continue;
+                }
+
+                let push = |side: &mut Vec<PolyVertex>, vert: PolyVertex| {
+                    if side.last().map(|last| last.vertex_id) != Some(vert.vertex_id) {
+                        side.push(vert);
+                    }
+                };
+                let (mut front_verts, mut back_verts) = (vec![], vec![]);
+                // where the polygon's outline crosses the plane, and whether it was on its way out of the front
+                let mut crossings = vec![];
+                for i in 0..poly.verts.len() {
+                    let (a, b) = (&poly.verts[i], &poly.verts[(i + 1) % poly.verts.len()]);
+                    push(if in_front(a) { &mut front_verts } else { &mut back_verts }, a.clone());
+                    if in_front(a) == in_front(b) {
+                        continue;
+                    }
+
+                    let (dist_a, dist_b) = (dists[a.vertex_id.0 as usize], dists[b.vertex_id.0 as usize]);
+                    let t = dist_a / (dist_a - dist_b);
+                    let vertex_id = match t {
+                        _ if t <= 0.0 => a.vertex_id,
+                        _ if t >= 1.0 => b.vertex_id,
+                        _ => *cut_verts
+                            .entry((a.vertex_id.min(b.vertex_id), a.vertex_id.max(b.vertex_id)))
+                            .or_insert_with(|| {
+                                verts.push(verts[a.vertex_id.0 as usize] * (1.0 - t) + verts[b.vertex_id.0 as usize] * t);
+                                VertexId(verts.len() as u32 - 1)
+                            }),
+                    };
+                    let normal_id = if a.normal_id == b.normal_id {
+                        a.normal_id
+                    } else {
+                        *cut_norms
+                            .entry((a.normal_id.min(b.normal_id), a.normal_id.max(b.normal_id), vertex_id))
+                            .or_insert_with(|| {
+                                let norm = norms[a.normal_id.0 as usize] * (1.0 - t) + norms[b.normal_id.0 as usize] * t;
+                                norms.push(if norm.is_null() { norm } else { norm.normalize() });
+                                NormalId(norms.len() as u32 - 1)
+                            })
+                    };
+                    let uv = (a.uv.0 + (b.uv.0 - a.uv.0) * t, a.uv.1 + (b.uv.1 - a.uv.1) * t);
+                    let cut = PolyVertex { vertex_id, normal_id, uv };
+                    push(&mut front_verts, cut.clone());
+                    push(&mut back_verts, cut);
+                    crossings.push((vertex_id, in_front(a)));
+                }
+
+                // each time the outline leaves the front, the front's side of it runs along the cut to where it comes back,
+                // so the cap has to run back the other way
+                for i in 0..crossings.len() {
+                    let ((exit, leaving), (entry, _)) = (crossings[i], crossings[(i + 1) % crossings.len()]);
+                    if leaving && exit != entry {
+                        cap_edges.push((entry, exit));
+                    }
+                }
+                for (mut side_verts, side) in [(front_verts, &mut front), (back_verts, &mut back)] {
+                    if side_verts.len() > 1 && side_verts.first().map(|vert| vert.vertex_id) == side_verts.last().map(|vert| vert.vertex_id) {
+                        side_verts.pop();
+                    }
+                    if side_verts.len() >= 3 {
+                        side.push(Polygon {
+                            normal: poly.normal,
+                            texture: poly.texture,
+                            verts: side_verts,
+                        });
+                    }
+                }
+            }
+            (front, back, cap_edges)
+        }
+
+        // fills in the loops the edges make with polygons facing `outward`, leaving out any edges that don't make it back around
+        // or nothing, if the loops are too tangled to fill
+        fn cap(
+            verts: &[Vec3d], norms: &mut Vec<Vec3d>, edges: &[(VertexId, VertexId)], outward: Vec3d, texture: TextureId, uv_scale: f32,
+        ) -> Option<Vec<Polygon>> {
+            let mut next = HashMap::<VertexId, Vec<VertexId>>::new();
+            for &(from, to) in edges.iter().rev() {
+                next.entry(from).or_default().push(to);
+            }
+            let mut loops = vec![];
+            for &(start, _) in edges {
+                let mut ring = vec![start];
+                while let Some(to) = next.get_mut(ring.last().unwrap()).and_then(Vec::pop) {
+                    if to == start {
+                        loops.push(std::mem::take(&mut ring));
+                        break;
+                    }
+                    ring.push(to);
+                }
+            }
+
+            // the loops come wound one way or the other depending on the polygons, the caps just have to match
+            let newell = |ring: &Vec<VertexId>| {
+                (0..ring.len())
+                    .map(|i| verts[ring[i].0 as usize].cross(&verts[ring[(i + 1) % ring.len()].0 as usize]))
+                    .fold(Vec3d::ZERO, |sum, cross| sum + cross)
+            };
+            let winding = loops.iter().map(|ring| newell(ring).dot(&outward)).sum::<f32>();
+            let winding_normal = if winding >= 0.0 { outward } else { -outward };
+            let ring_points = loops
+                .iter()
+                .map(|ring| ring.iter().map(|id| verts[id.0 as usize]).collect())
+                .collect::<Vec<_>>();
+            let ids = loops.concat();
+            let tris = geometry::triangulate(&ring_points, winding_normal)?;
+
+            norms.push(outward);
+            let normal_id = NormalId(norms.len() as u32 - 1);
+            let (u_axis, v_axis) = {
+                let (vec1, vec2) = (outward.cross(&Vec3d::new(0.0, 0.0, 1.0)), outward.cross(&Vec3d::new(1.0, 0.0, 0.0)));
+                let u_axis = if vec1.magnitude() > vec2.magnitude() { vec1 } else { vec2 }.normalize();
+                (u_axis, outward.cross(&u_axis))
+            };
+            let polygons = tris
+                .into_iter()
+                .map(|tri| Polygon {
+                    normal: outward,
+                    texture,
+                    verts: tri
+                        .iter()
+                        .map(|&i| {
+                            let vertex_id = ids[i as usize];
+                            let vert = verts[vertex_id.0 as usize];
+                            PolyVertex {
+                                vertex_id,
+                                normal_id,
+                                uv: (vert.dot(&u_axis) / uv_scale, vert.dot(&v_axis) / uv_scale),
+                            }
+                        })
+                        .collect(),
+                })
+                .collect();
+            Some(polygons)
+        }
+
+        let num_pieces = num_pieces.min(MAX_DEBRIS_OBJECTS.saturating_sub(self.num_debris_objects()) as usize);
+        let subobj = &self.sub_objects[id];
+        let polygons = subobj.bsp_data.collision_tree.leaves().map(|(_, poly)| poly.clone()).collect::<Vec<_>>();
+        if num_pieces == 0 || polygons.is_empty() {
+            return vec![];
+        }
+
+        // weld the vertices, so the cuts through neighboring polygons meet up
+        let (mut verts, mut norms) = (subobj.bsp_data.verts.clone(), subobj.bsp_data.norms.clone());
+        let mut welded = HashMap::new();
+        let welded = (verts.iter().enumerate())
+            .map(|(i, vert)| *welded.entry([vert.x, vert.y, vert.z].map(f32::to_bits)).or_insert(VertexId(i as u32)))
+            .collect::<Vec<_>>();
+        let mut polygons = polygons;
+        for vert in polygons.iter_mut().flat_map(|poly| &mut poly.verts) {
+            vert.vertex_id = welded[vert.vertex_id.0 as usize];
+        }
+
+        let texture = (polygons.iter().map(|poly| poly.texture).counts().into_iter())
+            .max_by_key(|&(texture, count)| (count, std::cmp::Reverse(texture)))
+            .map(|(texture, _)| texture)
+            .unwrap();
+        let uv_scale = subobj.bbox.size_on_axis(subobj.bbox.greatest_dimension()).max(1.0);
+        let origin = self.get_total_subobj_offset(id);
+
+        // xorshift, nothing fancy needed for picking cuts
+        let mut state = seed.max(1);
+        let mut random = move || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as f32 / u32::MAX as f32
+        };
+
+        let bbox_of = |verts: &[Vec3d], polygons: &[Polygon]| {
+            BoundingBox::from_vectors(polygons.iter().flat_map(|poly| &poly.verts).map(|vert| verts[vert.vertex_id.0 as usize]))
+        };
+        let mut pieces = vec![polygons];
+        let mut attempts = 0;
+        while pieces.len() < num_pieces && attempts < num_pieces * 4 {
+            attempts += 1;
+            let biggest = (0..pieces.len())
+                .max_by(|&a, &b| bbox_of(&verts, &pieces[a]).volume().total_cmp(&bbox_of(&verts, &pieces[b]).volume()))
+                .unwrap();
+            let bbox = bbox_of(&verts, &pieces[biggest]);
+
+            // roughly across the longest axis, somewhere around the middle
+            let axis = bbox.greatest_dimension();
+            let mut normal = Vec3d::new(random() - 0.5, random() - 0.5, random() - 0.5) * 0.8;
+            normal[axis] += 1.0;
+            let normal = normal.normalize();
+            let mut point = (bbox.min + bbox.max) / 2.0;
+            point[axis] += (random() - 0.5) * 0.4 * bbox.size_on_axis(axis);
+
+            let (mut front, mut back, cap_edges) = slice(&mut verts, &mut norms, pieces[biggest].clone(), point, normal);
+            if front.is_empty() || back.is_empty() {
+                continue;
+            }
+            let back_edges = cap_edges.iter().map(|&(from, to)| (to, from)).collect::<Vec<_>>();
+            // a cut the caps can't close up would leave the pieces open, so try somewhere else instead
+            let Some(front_cap) = cap(&verts, &mut norms, &cap_edges, -normal, texture, uv_scale) else {
+                continue;
+            };
+            let Some(back_cap) = cap(&verts, &mut norms, &back_edges, normal, texture, uv_scale) else {
+                continue;
+            };
+            front.extend(front_cap);
+            back.extend(back_cap);
+            pieces[biggest] = front;
+            pieces.push(back);
+        }
+
+        let mut new_ids = vec![];
+        for polygons in pieces {
+            let bbox = bbox_of(&verts, &polygons);
+            let center = (bbox.min + bbox.max) / 2.0;
+            let local_verts = verts.iter().map(|&vert| vert - center).collect::<Vec<_>>();
+            let new_id = ObjectId(self.sub_objects.len() as u32);
+            let name = (1..)
+                .map(|i| format!("debris{:02}", i))
+                .find(|new_name| !self.sub_objects.iter().any(|subobj| subobj.name == *new_name))
+                .unwrap();
+            let mut new_subobj = SubObject {
+                obj_id: new_id,
+                offset: origin + center,
+                name,
+                bsp_data: BspData::from_polygons(&local_verts, &norms, polygons),
+                is_debris_model: true,
+                ..Default::default()
+            };
+            new_subobj.bbox = new_subobj.recalc_bbox();
+            new_subobj.radius = new_subobj.recalc_radius();
+            self.sub_objects.push(new_subobj);
+            new_ids.push(new_id);
+        }
+
+        self.header.num_subobjects = self.sub_objects.len() as u32;
+        self.recalc_all_children_ids();
+        self.recalc_semantic_name_links();
+        self.recheck_warnings(Set::All);
+        self.recheck_errors(Set::All);
+        new_ids
+    }
+
+    /// Changes the version the model will be written as, returning what the model gives up (or would, when written) in the process.
+    ///
+    /// Nothing is removed from the model itself, so converting back before saving loses nothing.
+    pub fn convert_to_version(&mut self, target: Version) -> ConversionReport {
+        let report = self.conversion_report(target);
+        self.version = target;
+        self.recheck_warnings(Set::All);
+        self.recheck_errors(Set::All);
+        report
+    }
+
+    /// Lists every field which would be lost, rescaled or reinterpreted by writing the model as `target`, without changing anything
+    pub fn conversion_report(&self, target: Version) -> ConversionReport {
+        let mut changes = vec![];
+        let mut change = |kind, description: String| changes.push(ConversionChange { kind, description });
+        let from = self.version;
+
+        // header
+        if target < Version::V19_03 && from >= Version::V19_03 {
+            change(ConversionKind::Lost, format!("mass, center of mass and moment of inertia (FSO will assume a mass of 50)"));
+        } else if target < Version::V20_09 && from >= Version::V20_09 && self.header.mass > 0.0 {
+            let vol_mass = (self.header.mass / 4.65).powf(1.5);
+            change(
+                ConversionKind::Rescaled,
+                format!(
+                    "mass is stored as volume-based mass ({} becomes {}), and the moment of inertia scaled to match; both are converted back when loaded",
+                    self.header.mass, vol_mass
+                ),
+            );
+        }
+        if target < Version::V20_14 && !self.header.cross_sections.is_empty() {
+            change(ConversionKind::Lost, format!("{} cross sections", self.header.cross_sections.len()));
+        }
+        if target < Version::V20_07 && !self.header.bsp_lights.is_empty() {
+            change(ConversionKind::Lost, format!("{} muzzle flash/thruster lights", self.header.bsp_lights.len()));
+        }
+
+        // subobjects
+        if target < Version::V20_00 && from >= Version::V20_00 {
+            change(ConversionKind::Reinterpreted, format!("BSP bounding boxes aren't stored, they are recalculated when loaded"));
+        }
+        let translating = self
+            .sub_objects
+            .iter()
+            .filter(|subobj| subobj.translation_type != SubsysTranslationType::None);
+        let translating = translating.map(|subobj| subobj.name.as_str()).collect::<Vec<_>>();
+        if target < Version::V23_01 && !translating.is_empty() {
+            change(ConversionKind::Lost, format!("translation type and axis of subobjects {}", translating.join(", ")));
+        }
+        let limit = Self::max_verts_norms_for(target);
+        for subobj in &self.sub_objects {
+            let (num_verts, num_norms) = (subobj.bsp_data.verts.len(), subobj.bsp_data.norms.len());
+            if num_verts > limit || num_norms > limit {
+                change(
+                    ConversionKind::Blocker,
+                    format!("subobject {} has {} vertices and {} normals, more than the limit of {}", subobj.name, num_verts, num_norms, limit),
+                );
+            }
+        }
+
+        // paths
+        let num_path_parents = self.paths.iter().filter(|path| !path.parent.is_empty()).count();
+        if target < Version::V20_02 && num_path_parents > 0 {
+            change(ConversionKind::Lost, format!("parent subobject names of {} paths", num_path_parents));
+        }
+
+        // weapons
+        let has_offset_field = |version: Version| version >= Version::V21_18 && version != Version::V22_00;
+        let num_offsets = (self.primary_weps.iter().chain(&self.secondary_weps))
+            .flatten()
+            .filter(|point| point.offset != 0.0)
+            .count();
+        if !has_offset_field(target) && num_offsets > 0 {
+            change(ConversionKind::Lost, format!("external angle offsets of {} weapon points", num_offsets));
+        }
+
+        // thrusters
+        let num_bank_properties = self.thruster_banks.iter().filter(|bank| !bank.properties.is_empty()).count();
+        if target < Version::V21_17 && num_bank_properties > 0 {
+            change(ConversionKind::Lost, format!("properties of {} thruster banks", num_bank_properties));
+        }
+        let num_glow_radii = self
+            .thruster_banks
+            .iter()
+            .flat_map(|bank| &bank.glows)
+            .filter(|glow| glow.radius != 1.0)
+            .count();
+        if target <= Version::V20_04 && num_glow_radii > 0 {
+            change(ConversionKind::Lost, format!("radii of {} thruster glows (read back as 1.0)", num_glow_radii));
+        }
+
+        // shield
+        if let Some(shield) = &self.shield_data {
+            if target < Version::V21_18 {
+                if shield.collision_tree.is_some() {
+                    change(ConversionKind::Lost, format!("shield collision tree (FSO builds its own when loading)"));
+                }
+            } else if from >= Version::V21_18 && (target >= Version::V22_00) != (from >= Version::V22_00) {
+                let (old, new) = if target >= Version::V22_00 { ("SLDC", "SLC2") } else { ("SLC2", "SLDC") };
+                change(ConversionKind::Reinterpreted, format!("shield collision tree is stored as {} rather than {}", new, old));
+            }
+        }
+
+        ConversionReport { from, to: target, changes }
+    }
+
     pub fn global_import(&mut self, mut import_model: Box<Model>) {
         self.header.mass = import_model.header.mass;
         self.header.moment_of_inertia = import_model.header.moment_of_inertia;
@@ -2954,6 +5352,49 @@ impl Model {
     }
 }
 
+/// Everything that converting a model to another version does to its data, see [`Model::convert_to_version`]
+#[derive(Debug, Clone)]
+pub struct ConversionReport {
+    pub from: Version,
+    pub to: Version,
+    pub changes: Vec<ConversionChange>,
+}
+impl ConversionReport {
+    /// whether the model can't be written in the target version at all until something is fixed
+    pub fn is_blocked(&self) -> bool {
+        self.changes.iter().any(|change| change.kind == ConversionKind::Blocker)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConversionChange {
+    pub kind: ConversionKind,
+    pub description: String,
+}
+impl Display for ConversionChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.kind {
+            ConversionKind::Lost => "lost",
+            ConversionKind::Rescaled => "rescaled",
+            ConversionKind::Reinterpreted => "reinterpreted",
+            ConversionKind::Blocker => "cannot be written",
+        };
+        write!(f, "{}: {}", kind, self.description)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConversionKind {
+    /// the data isn't written at all in the target version
+    Lost,
+    /// the data is converted to different units on writing, and converted back on reading
+    Rescaled,
+    /// the data is stored in a different form, and may not read back exactly the same
+    Reinterpreted,
+    /// the model can't be written in the target version until this is fixed
+    Blocker,
+}
+
 pub enum Set<T> {
     All,
     One(T),
@@ -2980,7 +5421,11 @@ pub enum Warning {
     UntexturedPolygons,
     DockingBayWithoutPath(usize),
     ThrusterPropertiesInvalidVersion(usize),
-    WeaponOffsetInvalidVersion { primary: bool, bank: usize, point: usize },
+    WeaponOffsetInvalidVersion {
+        primary: bool,
+        bank: usize,
+        point: usize,
+    },
     SubObjectTranslationInvalidVersion(ObjectId),
     TooFewTurretFirePoints(usize),
     TooManyTurretFirePoints(usize),
@@ -2990,6 +5435,15 @@ pub enum Warning {
     TooManyTextures,
     InvalidDockParentSubmodel(usize),
     Detail0NonZeroOffset,
+    ZeroAreaPolygons(ObjectId),
+    NonPlanarPolygons(ObjectId),
+    ConcavePolygons(ObjectId),
+    SubObjectInterpenetration {
+        obj_id: ObjectId,
+        other: ObjectId,
+        depth: OrdF32,
+        swept: bool,
+    },
 
     PathNameTooLong(usize),
     SpecialPointNameTooLong(usize),
@@ -3001,6 +5455,18 @@ pub enum Warning {
     DockingBayPropertiesTooLong(usize),
     GlowBankPropertiesTooLong(usize),
     SpecialPointPropertiesTooLong(usize),
+
+    SubObjectPropertiesInvalid(ObjectId),
+    ThrusterPropertiesInvalid(usize),
+    DockingBayPropertiesInvalid(usize),
+    GlowBankPropertiesInvalid(usize),
+    SpecialPointPropertiesInvalid(usize),
+
+    ShieldOpenEdges,
+    ShieldNonManifoldEdges,
+    ShieldInconsistentWinding,
+    ShieldInwardNormals,
+    ShieldDegeneratePolygons,
     // path with no parent
     // thruster with no engine subsys (and an engine subsys exists)
     // turret uvec != turret normal
@@ -3102,3 +5568,7 @@ pub fn properties_set_flag(properties: &mut String, flag: &str) {
 pub fn properties_remove_flag(properties: &mut String, flag: &str) {
     properties_delete_field(properties, flag);
 }
+
+fn properties_invalid(properties: &str, kind: PropertiesKind) -> bool {
+    !Properties::parse(properties).validate(kind).is_empty()
+}
@@ -1,7 +1,9 @@
 use std::{
     borrow::Cow,
+    collections::BTreeSet,
     f32::consts::PI,
-    io::{self, Write},
+    fmt::{Debug, Display},
+    io::{self, ErrorKind, Write},
     mem::size_of,
 };
 
@@ -16,10 +18,79 @@ use json::Index;
 extern crate nalgebra_glm as glm;
 
 use crate::{
-    BoundingBox, BspData, BspNode, Dock, EyePoint, GlowPointBank, Insignia, Model, ObjVec, ObjectId, Path, ShieldData, ShieldNode, SpecialPoint,
-    SubObject, ThrusterBank, Turret, Vec3d, Version, WeaponHardpoint,
+    BoundingBox, BspData, BspNode, BspStrategy, Dock, Error, EyePoint, GlowPointBank, Insignia, Model, ObjVec, ObjectId, Path, ShieldData,
+    ShieldNode, SpecialPoint, SubObject, ThrusterBank, Turret, Vec3d, Version, WeaponHardpoint,
 };
 
+/// The ways in which writing a POF file can fail.
+pub enum WriteError {
+    /// The model has errors, and [`WriteOptions::force`] was not set
+    ModelHasErrors(BTreeSet<Error>),
+    /// Something in the model can't be represented in a POF of its version, such as a vertex index too large for it
+    Unrepresentable(String),
+    /// Any other I/O error from the underlying writer
+    Io(io::Error),
+}
+impl Display for WriteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WriteError::ModelHasErrors(errors) => write!(f, "the model has {} error(s): {:?}", errors.len(), errors),
+            WriteError::Unrepresentable(reason) => write!(f, "{}", reason),
+            WriteError::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+impl Debug for WriteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+impl std::error::Error for WriteError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WriteError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+impl From<io::Error> for WriteError {
+    fn from(err: io::Error) -> Self {
+        // the serializers can only return io errors, so anything else comes back wrapped in one
+        if err.get_ref().map_or(false, |inner| inner.is::<WriteError>()) {
+            *err.into_inner().unwrap().downcast().unwrap()
+        } else {
+            WriteError::Io(err)
+        }
+    }
+}
+impl From<WriteError> for io::Error {
+    fn from(err: WriteError) -> Self {
+        match err {
+            WriteError::Io(err) => err,
+            err => io::Error::new(ErrorKind::InvalidData, err),
+        }
+    }
+}
+
+fn unrepresentable(reason: String) -> io::Error {
+    WriteError::Unrepresentable(reason).into()
+}
+
+/// Controls what [`Model::write_with_options`] does beyond writing out the model exactly as it is.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WriteOptions {
+    /// Rebuild BSP trees and recompute bounding boxes and radii before writing, see [`Model::recalculate_geometry`]
+    pub recalculate: bool,
+    /// How to build the trees when `recalculate` is set
+    pub bsp_strategy: BspStrategy,
+    /// Leave out the PINF chunk of comments
+    pub strip_comments: bool,
+    /// Leave out chunks that would contain nothing of substance, such as weapon banks with no points, or a shield with no polygons
+    pub drop_empty_chunks: bool,
+    /// Write the model even if it has errors, which may produce a file FSO can't load
+    pub force: bool,
+}
+
 pub(crate) trait Serialize {
     fn write_to(&self, w: &mut impl Write) -> io::Result<()>;
 }
@@ -259,14 +330,23 @@ pub(crate) fn write_bsp_data(buf: &mut Vec<u8>, version: Version, bsp_data: &Bsp
                     let chunk_size_pointer = Fixup::new(buf, base)?;
 
                     poly.normal.write_to(buf)?;
-                    verts[poly.verts[0].vertex_id.0 as usize].write_to(buf)?; // center: unused now, but old fso versions needed this to be on the plane at least
+                    // center: unused now, but old fso versions needed this to be on the plane at least
+                    match poly.verts.first().and_then(|vert| verts.get(vert.vertex_id.0 as usize)) {
+                        Some(center) => center.write_to(buf)?,
+                        None => return Err(unrepresentable(format!("polygon has no vertices, or refers to a vertex which doesn't exist"))),
+                    }
                     0f32.write_to(buf)?; // radius: unused
                     (poly.verts.len() as u32).write_to(buf)?;
                     poly.texture.write_to(buf)?;
 
                     for vert in &poly.verts {
-                        u16::try_from(vert.vertex_id.0).unwrap().write_to(buf)?;
-                        u16::try_from(vert.normal_id.0).unwrap().write_to(buf)?;
+                        let too_large = |what, id| unrepresentable(format!("{} index {} is too large for version {}", what, id, version));
+                        u16::try_from(vert.vertex_id.0)
+                            .map_err(|_| too_large("vertex", vert.vertex_id.0))?
+                            .write_to(buf)?;
+                        u16::try_from(vert.normal_id.0)
+                            .map_err(|_| too_large("normal", vert.normal_id.0))?
+                            .write_to(buf)?;
                         vert.uv.write_to(buf)?;
                     }
 
@@ -293,6 +373,16 @@ pub(crate) fn write_bsp_data(buf: &mut Vec<u8>, version: Version, bsp_data: &Bsp
     (bsp_data.norms.len() as u32).write_to(buf)?;
     let vertex_data_offset = Fixup::new(buf, base)?;
 
+    // normals are stored as groups following each vertex, so there can only be so many of them
+    if bsp_data.norms.len() > bsp_data.verts.len() * MAX_NORMS_PER_VERT as usize {
+        return Err(unrepresentable(format!(
+            "{} normals is too many for {} vertices, there can be at most {} per vertex",
+            bsp_data.norms.len(),
+            bsp_data.verts.len(),
+            MAX_NORMS_PER_VERT
+        )));
+    }
+
     let mut num_norms = bsp_data.norms.len();
     for _ in 0..bsp_data.verts.len() {
         let old_num_norms = num_norms;
@@ -300,7 +390,6 @@ pub(crate) fn write_bsp_data(buf: &mut Vec<u8>, version: Version, bsp_data: &Bsp
 
         buf.push((old_num_norms - num_norms) as u8);
     }
-    assert!(num_norms == 0);
 
     align_buf(buf)?;
 
@@ -375,7 +464,33 @@ fn write_subobjects(w: &mut impl Write, chunk_name: &[u8; 4], objects: &[SubObje
 }
 
 impl Model {
+    /// Writes the model out exactly as it is.
     pub fn write(&self, w: &mut impl Write) -> io::Result<()> {
+        self.write_chunks(w, &WriteOptions::default())
+    }
+
+    /// Writes the model, optionally cleaning it up first, see [`WriteOptions`].
+    ///
+    /// The model itself is left untouched; any recalculation is done on a copy.
+    pub fn write_with_options(&self, w: &mut impl Write, options: &WriteOptions) -> Result<(), WriteError> {
+        let recalculated;
+        let model = if options.recalculate {
+            let mut model = self.clone();
+            model.recalculate_geometry(options.bsp_strategy);
+            recalculated = model;
+            &recalculated
+        } else {
+            self
+        };
+
+        if !options.force && !model.errors.is_empty() {
+            return Err(WriteError::ModelHasErrors(model.errors.clone()));
+        }
+
+        Ok(model.write_chunks(w, options)?)
+    }
+
+    fn write_chunks(&self, w: &mut impl Write, options: &WriteOptions) -> io::Result<()> {
         // set the version to be using be all the serializers
         crate::VERSION.with(|f| {
             f.set(self.version);
@@ -427,18 +542,23 @@ impl Model {
         write_chunk_vec(w, b"PATH", &self.paths)?;
         write_chunk_vec(w, b"SPCL", &self.special_points)?;
         write_chunk_vec(w, b"EYE ", &self.eye_points)?;
-        if !self.primary_weps.is_empty() {
+        let drop_banks = |banks: &[Vec<WeaponHardpoint>]| options.drop_empty_chunks && banks.iter().all(|bank| bank.is_empty());
+        if !self.primary_weps.is_empty() && !drop_banks(&self.primary_weps) {
             write_chunk(w, b"GPNT", Some(&self.primary_weps))?;
         }
-        if !self.secondary_weps.is_empty() {
+        if !self.secondary_weps.is_empty() && !drop_banks(&self.secondary_weps) {
             write_chunk(w, b"MPNT", Some(&self.secondary_weps))?;
         }
         if !self.turrets.is_empty() {
             write_chunk(w, b"TGUN", Some(&self.turrets))?;
         }
-        write_chunk_vec(w, b"FUEL", &self.thruster_banks)?;
-        write_chunk_vec(w, b"GLOW", &self.glow_banks)?;
-        if !self.comments.is_empty() {
+        if !(options.drop_empty_chunks && self.thruster_banks.iter().all(|bank| bank.glows.is_empty() && bank.properties.is_empty())) {
+            write_chunk_vec(w, b"FUEL", &self.thruster_banks)?;
+        }
+        if !(options.drop_empty_chunks && self.glow_banks.iter().all(|bank| bank.glow_points.is_empty())) {
+            write_chunk_vec(w, b"GLOW", &self.glow_banks)?;
+        }
+        if !self.comments.is_empty() && !options.strip_comments {
             write_chunk_raw(w, b"PINF", |w| {
                 let padding_length = (3_usize.wrapping_sub(self.comments.len()) % 4) + 1;
                 w.write_all(self.comments.as_bytes())?;
@@ -447,8 +567,14 @@ impl Model {
             })?;
         }
         write_chunk_vec(w, b"DOCK", &self.docking_bays)?;
-        write_chunk_vec(w, b"INSG", &self.insignias)?;
-        if let Some(shield_data) = &self.shield_data {
+        if !(options.drop_empty_chunks && self.insignias.iter().all(|insignia| insignia.faces.is_empty())) {
+            write_chunk_vec(w, b"INSG", &self.insignias)?;
+        }
+        let shield_data = self
+            .shield_data
+            .as_ref()
+            .filter(|shield| !(options.drop_empty_chunks && shield.polygons.is_empty()));
+        if let Some(shield_data) = shield_data {
             write_chunk_raw(w, b"SHLD", |w| {
                 shield_data.verts.write_to(w)?;
                 shield_data.polygons.write_to(w)
@@ -461,6 +587,9 @@ impl Model {
         if self.visual_center != Vec3d::default() {
             write_chunk(w, b"ACEN", Some(&self.visual_center))?;
         }
+        for chunk in &self.unknown_chunks {
+            write_chunk_raw(w, &chunk.id, |w| w.write_all(&chunk.data))?;
+        }
 
         Ok(())
     }
@@ -20,10 +20,10 @@ use eframe::egui::PointerButton;
 use egui::{Color32, RichText, TextEdit, ViewportId};
 use glium::{glutin::surface::WindowSurface, texture::SrgbTexture2d, BlendingFunction, Display, IndexBuffer, LinearBlendingFactor, VertexBuffer};
 use glm::{Mat4x4, TMat4};
-use native_dialog::FileDialog;
+use native_dialog::{FileDialog, MessageDialog, MessageType};
 use pof::{
-    properties_get_field, BspData, Insignia, NameLink, NormalId, NormalVec3, ObjVec, ObjectId, Parser, PolyVertex, Polygon, Set, ShieldData,
-    SubObject, TextureId, Vec3d, VertexId,
+    properties_get_field, BspData, ImportError, ImportReport, Insignia, NameLink, NormalId, NormalVec3, ObjVec, ObjectId, ParseError, ParseOptions,
+    Parser, PolyVertex, Polygon, Set, ShieldData, SubObject, TextureId, Vec3d, VertexId,
 };
 use simplelog::*;
 use std::{
@@ -37,6 +37,7 @@ use std::{
     time::Duration,
 };
 use ui::{PofToolsGui, TreeValue};
+use ui_properties_panel::PropertiesPanel;
 use winit::window::Window;
 
 mod primitives;
@@ -389,10 +390,7 @@ impl Model {
             }
         }
 
-        for id in 0..self.pof_model.sub_objects.len() {
-            let id = ObjectId(id as u32);
-            self.pof_model.apply_subobj_transform_mesh(id, &self.subobject_transform_matrix[id]);
-        }
+        self.pof_model.apply_subobj_transforms_mesh(&self.subobject_transform_matrix);
 
         self.pof_model.clean_up();
     }
@@ -449,27 +447,112 @@ impl PofToolsGui {
                     .unwrap()
             });
 
-            path.map(|path| {
-                let ext = path.extension().map(|ext| ext.to_ascii_lowercase());
-                let filename = path.file_name().and_then(|f| f.to_str()).unwrap_or("").to_string();
-                info!("Attempting to load {}", filename);
-                Box::new(Model {
-                    pof_model: match ext.as_ref().and_then(|ext| ext.to_str()) {
-                        Some("dae") => pof::parse_dae(path),
-                        Some("gltf" | "glb") => pof::parse_gltf(path),
-                        Some("pof") => {
-                            let file = File::open(&path).expect("TODO invalid file or smth i dunno");
-                            let mut parser = Parser::new(file).expect("TODO invalid version of file or smth i dunno");
-                            parser.parse(path).expect("TODO invalid pof file or smth i dunno")
-                        }
-                        _ => todo!(),
-                    },
-                    texture_map: HashMap::new(),
-                    subobject_transform_matrix: ObjVec::default(),
-                })
-            })
+            let Some(path) = path else { return Ok(None) };
+            let ext = path.extension().map(|ext| ext.to_ascii_lowercase());
+            let filename = path.file_name().and_then(|f| f.to_str()).unwrap_or("").to_string();
+            info!("Attempting to load {}", filename);
+            Ok(Some(Box::new(Model {
+                pof_model: match ext.as_ref().and_then(|ext| ext.to_str()) {
+                    Some("dae") => Self::finish_import(pof::parse_dae(path), &filename)?,
+                    Some("gltf" | "glb") => Self::finish_import(pof::parse_gltf(path), &filename)?,
+                    Some("pof") => Self::load_pof(path, &filename)?,
+                    _ => todo!(),
+                },
+                texture_map: HashMap::new(),
+                subobject_transform_matrix: ObjVec::default(),
+            })))
         });
-        model.map_err(|panic| *panic.downcast().unwrap())
+        model.unwrap_or_else(|panic| Err(*panic.downcast().unwrap()))
+    }
+
+    /// Parses a POF, offering to salvage what it can if the file turns out to be damaged. Must be run off the main thread.
+    fn load_pof(path: PathBuf, filename: &str) -> Result<pof::Model, String> {
+        let parse = |options: &ParseOptions| {
+            File::open(&path)
+                .map_err(ParseError::from)
+                .and_then(|file| Parser::new(file)?.parse_with_options(path.clone(), options))
+        };
+
+        let err = match parse(&ParseOptions::default()) {
+            Ok((model, _)) => return Ok(model),
+            Err(err @ (ParseError::BadMagic(_) | ParseError::UnsupportedVersion(_) | ParseError::Io(_))) => {
+                return Err(Self::report_load_error(filename, &err))
+            }
+            Err(err) => err,
+        };
+
+        let msg = format!("Failed to load {}: {}", filename, err);
+        error!("{}", msg);
+        let recover = MessageDialog::new()
+            .set_type(MessageType::Warning)
+            .set_title("Failed to load model")
+            .set_text(&format!("{}\n\nThe file appears to be damaged. Try to recover as much of it as possible?", msg))
+            .show_confirm()
+            .unwrap_or(false);
+        if !recover {
+            return Err(msg);
+        }
+
+        let (model, diagnostics) = parse(&ParseOptions { lenient: true }).map_err(|err| Self::report_load_error(filename, &err))?;
+
+        Self::report_diagnostics(
+            "Model recovered",
+            format!("{} was recovered with {} repair(s):", filename, diagnostics.len()),
+            &diagnostics,
+            "Check the model over before saving it.",
+        );
+
+        Ok(model)
+    }
+
+    /// Tells the user about anything that didn't come through from a DAE or glTF file. Must be run off the main thread.
+    fn finish_import(result: Result<(pof::Model, ImportReport), ImportError>, filename: &str) -> Result<pof::Model, String> {
+        let (model, report) = result.map_err(|err| Self::report_load_error(filename, &err))?;
+        if !report.is_empty() {
+            Self::report_diagnostics(
+                "Model imported",
+                format!("{} was imported, but {} node(s) did not come through as-is:", filename, report.diagnostics.len()),
+                &report.diagnostics,
+                "Check the node names and export settings in your modeling program.",
+            );
+        }
+        Ok(model)
+    }
+
+    /// Shows a list of diagnostics to the user. Must be run off the main thread.
+    fn report_diagnostics(title: &str, summary: String, diagnostics: &[impl std::fmt::Display], advice: &str) {
+        // the full list went to the log as it was recorded, just give the gist here
+        const MAX_SHOWN: usize = 15;
+        let mut text = summary + "\n";
+        for diagnostic in diagnostics.iter().take(MAX_SHOWN) {
+            text += &format!("\n- {}", diagnostic);
+        }
+        if diagnostics.len() > MAX_SHOWN {
+            text += &format!("\n...and {} more, see the log", diagnostics.len() - MAX_SHOWN);
+        }
+        text += "\n\n";
+        text += advice;
+        drop(
+            MessageDialog::new()
+                .set_type(MessageType::Info)
+                .set_title(title)
+                .set_text(&text)
+                .show_alert(),
+        );
+    }
+
+    /// Logs a model loading failure and tells the user about it. Must be run off the main thread.
+    fn report_load_error(filename: &str, err: &dyn std::fmt::Display) -> String {
+        let msg = format!("Failed to load {}: {}", filename, err);
+        error!("{}", msg);
+        drop(
+            MessageDialog::new()
+                .set_type(MessageType::Error)
+                .set_title("Failed to load model")
+                .set_text(&msg)
+                .show_alert(),
+        );
+        msg
     }
 
     /// same as `handle_model_loading_thread` but for the import model
@@ -552,6 +635,7 @@ impl PofToolsGui {
         }
         self.import_window = Default::default();
         self.ui_state.tree_view_selection = Default::default();
+        self.ui_state.symmetry_report = None;
         self.ui_state.refresh_properties_panel(&self.model);
         self.camera_heading = 2.7;
         self.camera_pitch = -0.4;
@@ -637,6 +721,15 @@ impl PofToolsGui {
         }
     }
 
+    /// creates buffers for subobjects newly added onto the end of the model
+    pub fn add_subobj_buffers(&mut self, display: &Display<WindowSurface>, ids: &[ObjectId]) {
+        for &id in ids {
+            self.buffer_objects
+                .push(GlObjectBuffers::new(display, &self.model.sub_objects[id], self.model.textures.len()));
+            self.model.subobject_transform_matrix.push(glm::identity());
+        }
+    }
+
     pub fn rebuild_all_subobj_buffers(&mut self, display: &Display<WindowSurface>) {
         let ids = (0..self.model.sub_objects.len()).map(|idx| ObjectId(idx as u32)).collect();
         self.rebuild_subobj_buffers(display, ids);
@@ -2132,6 +2225,30 @@ impl PofToolsGui {
                         })
                     }),
                 );
+
+                // and the coverage cone, if it's been checked, from clear green to fully blocked red
+                if let (Some(turret_idx), PropertiesPanel::Turret { coverage: Some(coverage), .. }) =
+                    (selected_turret, &self.ui_state.properties_panel)
+                {
+                    const COLORS: [[f32; 4]; 4] = [
+                        [0.15, 1.0, 0.15, 0.15],
+                        [0.8, 1.0, 0.15, 0.15],
+                        [1.0, 0.5, 0.15, 0.15],
+                        [1.0, 0.15, 0.15, 0.15],
+                    ];
+                    let turret = &model.turrets[turret_idx];
+                    let origin = Vec3d::average(turret.fire_points.iter().copied()) + model.get_total_subobj_offset(turret.gun_obj);
+                    let length = model.header.max_radius * 0.2;
+                    self.lollipops.extend(build_lollipops(
+                        &COLORS,
+                        display,
+                        coverage.directions.iter().zip(&coverage.blocked).map(|(&dir, &blocked)| {
+                            let selection = (blocked * (COLORS.len() - 1) as f32).round() as usize;
+                            // the ball goes at the far end, with the stick pointing back at the turret
+                            (origin + dir * length, dir * -length, size * 0.5, selection)
+                        }),
+                    ));
+                }
             }
             TreeValue::Paths(path_selection) => {
                 let mut selected_path = None;
@@ -4,7 +4,10 @@ use glium::{
     texture::{RawImage2d, SrgbTexture2d},
     Display,
 };
-use pof::{properties_get_field, Error, NormalVec3, ObjVec, Set, SubObject, TextureId, Vec3d, Version, Warning, WeaponHardpoint};
+use pof::{
+    properties_get_field, Axis, Error, MirrorItem, MirrorNaming, NormalVec3, ObjVec, Properties, PropertiesKind, Set, SubObject, SymmetryMismatch,
+    TextureId, Vec3d, Version, Warning, WeaponHardpoint,
+};
 use std::{
     collections::HashMap,
     f32::consts::{FRAC_PI_2, PI},
@@ -94,6 +97,29 @@ impl TreeValue {
             _ => None,
         }
     }
+    pub fn to_mirror_item(self) -> Option<MirrorItem> {
+        match self {
+            TreeValue::Weapons(WeaponTreeValue::PriBank(i)) => Some(MirrorItem::PrimaryBank(i)),
+            TreeValue::Weapons(WeaponTreeValue::SecBank(i)) => Some(MirrorItem::SecondaryBank(i)),
+            TreeValue::Glows(GlowTreeValue::Bank(i)) => Some(MirrorItem::GlowBank(i)),
+            TreeValue::Thrusters(ThrusterTreeValue::Bank(i)) => Some(MirrorItem::ThrusterBank(i)),
+            TreeValue::SpecialPoints(SpecialPointTreeValue::Point(i)) => Some(MirrorItem::SpecialPoint(i)),
+            TreeValue::Paths(PathTreeValue::Path(i)) => Some(MirrorItem::Path(i)),
+            TreeValue::SubObjects(SubObjectTreeValue::SubObject(id)) => Some(MirrorItem::SubObject(id)),
+            _ => None,
+        }
+    }
+    pub fn from_mirror_item(item: MirrorItem) -> TreeValue {
+        match item {
+            MirrorItem::PrimaryBank(i) => TreeValue::Weapons(WeaponTreeValue::PriBank(i)),
+            MirrorItem::SecondaryBank(i) => TreeValue::Weapons(WeaponTreeValue::SecBank(i)),
+            MirrorItem::GlowBank(i) => TreeValue::Glows(GlowTreeValue::Bank(i)),
+            MirrorItem::ThrusterBank(i) => TreeValue::Thrusters(ThrusterTreeValue::Bank(i)),
+            MirrorItem::SpecialPoint(i) => TreeValue::SpecialPoints(SpecialPointTreeValue::Point(i)),
+            MirrorItem::Path(i) => TreeValue::Paths(PathTreeValue::Path(i)),
+            MirrorItem::SubObject(id) => TreeValue::SubObjects(SubObjectTreeValue::SubObject(id)),
+        }
+    }
     // returns what, if any, tree_value best corresponds to a given error
     fn from_error(error: &Error) -> Option<TreeValue> {
         match error {
@@ -118,6 +144,10 @@ impl TreeValue {
             Warning::BBoxTooSmall(Some(id)) => Some(TreeValue::SubObjects(SubObjectTreeValue::SubObject(*id))),
             Warning::InvertedBBox(Some(id)) => Some(TreeValue::SubObjects(SubObjectTreeValue::SubObject(*id))),
             Warning::SubObjectTranslationInvalidVersion(id) => Some(TreeValue::SubObjects(SubObjectTreeValue::SubObject(*id))),
+            Warning::ZeroAreaPolygons(id) | Warning::NonPlanarPolygons(id) | Warning::ConcavePolygons(id) => {
+                Some(TreeValue::SubObjects(SubObjectTreeValue::SubObject(*id)))
+            }
+            Warning::SubObjectInterpenetration { obj_id, .. } => Some(TreeValue::SubObjects(SubObjectTreeValue::SubObject(*obj_id))),
             Warning::UntexturedPolygons => Some(TreeValue::Textures(TextureTreeValue::tex(model.untextured_idx))),
             Warning::DockingBayWithoutPath(idx) => Some(TreeValue::DockingBays(DockingTreeValue::Bay(*idx))),
             Warning::ThrusterPropertiesInvalidVersion(idx) => Some(TreeValue::Thrusters(ThrusterTreeValue::Bank(*idx))),
@@ -143,8 +173,18 @@ impl TreeValue {
             Warning::DockingBayPropertiesTooLong(idx) => Some(TreeValue::DockingBays(DockingTreeValue::Bay(*idx))),
             Warning::GlowBankPropertiesTooLong(idx) => Some(TreeValue::Glows(GlowTreeValue::Bank(*idx))),
             Warning::SpecialPointPropertiesTooLong(idx) => Some(TreeValue::SpecialPoints(SpecialPointTreeValue::Point(*idx))),
+            Warning::SubObjectPropertiesInvalid(id) => Some(TreeValue::SubObjects(SubObjectTreeValue::SubObject(*id))),
+            Warning::ThrusterPropertiesInvalid(idx) => Some(TreeValue::Thrusters(ThrusterTreeValue::Bank(*idx))),
+            Warning::DockingBayPropertiesInvalid(idx) => Some(TreeValue::DockingBays(DockingTreeValue::Bay(*idx))),
+            Warning::GlowBankPropertiesInvalid(idx) => Some(TreeValue::Glows(GlowTreeValue::Bank(*idx))),
+            Warning::SpecialPointPropertiesInvalid(idx) => Some(TreeValue::SpecialPoints(SpecialPointTreeValue::Point(*idx))),
             Warning::InvalidDockParentSubmodel(idx) => Some(TreeValue::DockingBays(DockingTreeValue::Bay(*idx))),
             Warning::Detail0NonZeroOffset => Some(TreeValue::SubObjects(SubObjectTreeValue::SubObject(model.header.detail_levels[0]))),
+            Warning::ShieldOpenEdges
+            | Warning::ShieldNonManifoldEdges
+            | Warning::ShieldInconsistentWinding
+            | Warning::ShieldInwardNormals
+            | Warning::ShieldDegeneratePolygons => Some(TreeValue::Shield),
         }
     }
 
@@ -516,6 +556,15 @@ pub struct UiState {
     pub display_origin: bool,
     pub display_uvec_fvec: bool,
     pub move_only_offset: bool,
+    /// how the selected point gets snapped onto the hull
+    pub snap_along_normal: bool,
+    pub snap_set_normal: bool,
+    pub snap_offset: f32,
+    /// how the selected item gets mirrored, and what the symmetry check looks at
+    pub mirror_axis: Axis,
+    pub mirror_naming: MirrorNaming,
+    pub symmetry_tolerance: f32,
+    pub symmetry_report: Option<Vec<SymmetryMismatch>>,
 }
 
 pub(crate) struct PofToolsGui {
@@ -582,7 +631,7 @@ impl PofToolsGui {
             model_loading_thread: Default::default(),
             texture_loading_thread: Default::default(),
             glow_point_sim_start: std::time::Instant::now(),
-            ui_state: Default::default(),
+            ui_state: UiState { symmetry_tolerance: 0.01, ..Default::default() },
             display_mode: DisplayMode::Textured,
             always_show_bbox: false,
             always_show_offset: false,
@@ -937,21 +986,37 @@ impl PofToolsGui {
                 ui.separator();
 
                 ui.menu_button(RichText::new(format!("Version: {}", self.model.version)).text_style(TextStyle::Button), |ui| {
-                    let mut changed = false;
+                    let mut new_version = None;
                     Version::for_each(|version| {
                         if version >= Version::V21_16 {
-                            changed |= ui
-                                .radio_value(&mut self.model.version, version, version.to_str())
-                                .on_hover_text(version.documentation())
-                                .changed();
+                            let response = ui.radio(self.model.version == version, version.to_str()).on_hover_ui(|ui| {
+                                ui.label(version.documentation());
+                                let report = self.model.conversion_report(version);
+                                if !report.changes.is_empty() {
+                                    ui.separator();
+                                    ui.label(RichText::new("Converting to this version:").weak());
+                                    for change in &report.changes {
+                                        let color = if change.kind == pof::ConversionKind::Blocker {
+                                            ERROR_RED
+                                        } else {
+                                            WARNING_YELLOW
+                                        };
+                                        ui.label(RichText::new(format!("- {}", change)).color(color));
+                                    }
+                                }
+                            });
+                            if response.clicked() && self.model.version != version {
+                                new_version = Some(version);
+                            }
                         }
                     });
 
-                    // we only need to recheck verson-specific warnings, but since those are parameterized, there's no easy way to say
-                    // 'those specific warnings but for all their parameters' so just do them all i guess
-                    if changed {
-                        self.model.recheck_warnings(pof::Set::All); // FIX
-                        self.model.recheck_errors(pof::Set::All);
+                    if let Some(version) = new_version {
+                        let report = self.model.convert_to_version(version);
+                        info!("Converted model from version {} to {}", report.from, report.to);
+                        for change in &report.changes {
+                            warn!("- {}", change);
+                        }
                     }
                 });
 
@@ -1215,6 +1280,30 @@ impl PofToolsGui {
                                     let id = self.model.header.detail_levels[0];
                                     format!("⚠ Detail0 object '{}' should have a (0, 0, 0) offset.", self.model.sub_objects[id].name)
                                 }
+                                Warning::ZeroAreaPolygons(id) => {
+                                    format!("⚠ Subobject {} has polygons with no area", self.model.sub_objects[*id].name)
+                                }
+                                Warning::NonPlanarPolygons(id) => {
+                                    format!("⚠ Subobject {} has polygons whose corners don't lie on one plane", self.model.sub_objects[*id].name)
+                                }
+                                Warning::ConcavePolygons(id) => {
+                                    format!("⚠ Subobject {} has concave polygons, which will not render correctly", self.model.sub_objects[*id].name)
+                                }
+                                Warning::SubObjectInterpenetration { obj_id, other, depth, swept: false } => format!(
+                                    "⚠ Subobjects {} and {} reach {:.3} into each other",
+                                    self.model.sub_objects[*obj_id].name, self.model.sub_objects[*other].name, depth
+                                ),
+                                Warning::SubObjectInterpenetration { obj_id, other, depth, swept: true } => format!(
+                                    "⚠ Subobject {} reaches {:.3} into {} as it rotates",
+                                    self.model.sub_objects[*obj_id].name, self.model.sub_objects[*other].name, depth
+                                ),
+                                Warning::ShieldOpenEdges => format!("⚠ The shield mesh has holes in it, shots can slip through its open edges"),
+                                Warning::ShieldNonManifoldEdges => format!("⚠ The shield mesh has edges shared by more than two polygons"),
+                                Warning::ShieldInconsistentWinding => {
+                                    format!("⚠ The shield mesh has neighboring polygons wound in opposite directions")
+                                }
+                                Warning::ShieldInwardNormals => format!("⚠ The shield mesh has polygons facing inwards"),
+                                Warning::ShieldDegeneratePolygons => format!("⚠ The shield mesh has polygons with no area"),
                                 Warning::PathNameTooLong(_)
                                 | Warning::SubObjectNameTooLong(_)
                                 | Warning::SpecialPointNameTooLong(_)
@@ -1266,6 +1355,40 @@ impl PofToolsGui {
                                     };
                                     format!("⚠ {} is too long (max {} bytes)", field, pof::MAX_PROPERTIES_LEN)
                                 }
+                                Warning::GlowBankPropertiesInvalid(_)
+                                | Warning::ThrusterPropertiesInvalid(_)
+                                | Warning::SubObjectPropertiesInvalid(_)
+                                | Warning::DockingBayPropertiesInvalid(_)
+                                | Warning::SpecialPointPropertiesInvalid(_) => {
+                                    let (field, properties, kind) = match warning {
+                                        Warning::GlowBankPropertiesInvalid(idx) => {
+                                            (format!("Glow bank {}", idx), &self.model.glow_banks[*idx].properties, PropertiesKind::GlowBank)
+                                        }
+                                        Warning::ThrusterPropertiesInvalid(idx) => (
+                                            format!("Thruster bank {}", idx + 1),
+                                            &self.model.thruster_banks[*idx].properties,
+                                            PropertiesKind::ThrusterBank,
+                                        ),
+                                        Warning::SubObjectPropertiesInvalid(id) => (
+                                            format!("Subobject {}", self.model.sub_objects[*id].name),
+                                            &self.model.sub_objects[*id].properties,
+                                            PropertiesKind::SubObject,
+                                        ),
+                                        Warning::DockingBayPropertiesInvalid(idx) => (
+                                            format!("Docking bay {}", idx + 1),
+                                            &self.model.docking_bays[*idx].properties,
+                                            PropertiesKind::DockingBay,
+                                        ),
+                                        Warning::SpecialPointPropertiesInvalid(idx) => (
+                                            format!("Special point {}", self.model.special_points[*idx].name),
+                                            &self.model.special_points[*idx].properties,
+                                            PropertiesKind::SpecialPoint,
+                                        ),
+                                        _ => unreachable!(),
+                                    };
+                                    let issues = Properties::parse(properties).validate(kind);
+                                    format!("⚠ {} properties: {}", field, issues.iter().map(|issue| issue.to_string()).collect::<Vec<_>>().join("; "))
+                                }
                             };
 
                             let text = RichText::new(str).text_style(TextStyle::Button).color(WARNING_YELLOW);
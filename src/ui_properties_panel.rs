@@ -11,8 +11,8 @@ use glium::glutin::surface::WindowSurface;
 use glium::Display;
 use nalgebra_glm::TMat4;
 use pof::{
-    Dock, Error, NormalVec3, ObjectId, PathId, Set::*, SubsysRotationAxis, SubsysRotationType, SubsysTranslationAxis, SubsysTranslationType, Vec3d,
-    Warning,
+    Axis, Dock, Error, MassProperties, MirrorItem, NormalVec3, ObjectId, PathId, Properties, PropertiesKind, PropertyDef, PropertyType,
+    RaycastOptions, Set::*, SubsysRotationAxis, SubsysRotationType, SubsysTranslationAxis, SubsysTranslationType, TurretCoverage, Vec3d, Warning,
 };
 
 use crate::Model;
@@ -153,6 +153,23 @@ pub fn visibility_button(ui: &mut Ui, toggled: bool) -> Response {
     res
 }
 
+// how an item gets referred to in the symmetry check's report
+fn mirror_item_name(model: &Model, item: MirrorItem) -> String {
+    match item {
+        MirrorItem::PrimaryBank(i) => format!("Primary bank {}", i + 1),
+        MirrorItem::SecondaryBank(i) => format!("Secondary bank {}", i + 1),
+        MirrorItem::GlowBank(i) => format!("Glow bank {}", i + 1),
+        MirrorItem::ThrusterBank(i) => format!("Thruster bank {}", i + 1),
+        // the report may be out of date by now
+        MirrorItem::SpecialPoint(i) => model
+            .special_points
+            .get(i)
+            .map_or(format!("Special point {}", i + 1), |point| point.name.clone()),
+        MirrorItem::Path(i) => model.paths.get(i).map_or(format!("Path {}", i + 1), |path| path.name.clone()),
+        MirrorItem::SubObject(id) => (model.sub_objects.get(id.0 as usize)).map_or(format!("Subobject {}", id.0 + 1), |subobj| subobj.name.clone()),
+    }
+}
+
 fn text_edit_single_no_undo(ui: &mut Ui, id: impl Hash, string: &mut String) -> Response {
     let id = egui::Id::new(id);
     let text_edit = egui::TextEdit::singleline(string).id(id);
@@ -216,7 +233,137 @@ fn text_edit_multi(
     response
 }
 
+/// a row per property, with an editor suited to its type, unknown keys and bad values in yellow, and a way to add the keys that are missing
+/// returns whether the properties were changed
+fn properties_editor(
+    ui: &mut Ui, id: String, kind: PropertiesKind, model: &mut Model, mut string_path: PathFunction<String>,
+    undo_history: &mut undo::History<UndoAction>,
+) -> bool {
+    enum Edit {
+        Value(usize, String),
+        Rename(usize, &'static str),
+        Remove(usize),
+        Add(&'static PropertyDef),
+    }
+
+    let mut old_val = string_path(model).clone();
+    let mut properties = Properties::parse(&old_val);
+    let mut edit = None;
+
+    egui::Grid::new(&id).num_columns(3).show(ui, |ui| {
+        for (i, entry) in properties.entries.iter().enumerate() {
+            let value = entry.value.as_deref().unwrap_or_default();
+            match kind.find(&entry.key) {
+                Some(def) => {
+                    ui.label(&entry.key).on_hover_text(def.doc);
+                    let valid = def.ty.parse(value).is_some();
+                    match def.ty {
+                        PropertyType::Flag => {
+                            ui.label("");
+                        }
+                        PropertyType::Choice(choices) => {
+                            let text = RichText::new(value).color(if valid { ui.visuals().text_color() } else { WARNING_YELLOW });
+                            egui::ComboBox::from_id_source(format!("{} {}", id, i))
+                                .selected_text(text)
+                                .show_ui(ui, |ui| {
+                                    for &choice in choices {
+                                        if ui.selectable_label(choice == value, choice).clicked() {
+                                            edit = Some(Edit::Value(i, choice.to_string()));
+                                        }
+                                    }
+                                });
+                        }
+                        _ => {
+                            let mut value = value.to_string();
+                            if !valid {
+                                UiState::set_widget_color(ui, WARNING_YELLOW);
+                            }
+                            if ui
+                                .add(TextEdit::singleline(&mut value).id(egui::Id::new(format!("{} {}", id, i))))
+                                .changed()
+                            {
+                                edit = Some(Edit::Value(i, value));
+                            }
+                            UiState::reset_widget_color(ui);
+                        }
+                    }
+                }
+                None => {
+                    ui.label(RichText::new(&entry.key).color(WARNING_YELLOW))
+                        .on_hover_text("FSO doesn't read this key here");
+                    ui.horizontal(|ui| {
+                        let mut value = value.to_string();
+                        if ui
+                            .add(TextEdit::singleline(&mut value).id(egui::Id::new(format!("{} {}", id, i))))
+                            .changed()
+                        {
+                            edit = Some(Edit::Value(i, value));
+                        }
+                        if let Some(def) = kind.find_similar(&entry.key) {
+                            if ui.button(format!("→ {}", def.key)).on_hover_text(def.doc).clicked() {
+                                edit = Some(Edit::Rename(i, def.key));
+                            }
+                        }
+                    });
+                }
+            }
+            if ui.button("🗑").on_hover_text("Remove this property").clicked() {
+                edit = Some(Edit::Remove(i));
+            }
+            ui.end_row();
+        }
+    });
+
+    egui::ComboBox::from_id_source(format!("{} add", id))
+        .selected_text("Add Property")
+        .show_ui(ui, |ui| {
+            for def in kind.known_properties() {
+                if properties.get(def.key).is_none() && ui.selectable_label(false, def.key).on_hover_text(def.doc).clicked() {
+                    edit = Some(Edit::Add(def));
+                }
+            }
+        });
+
+    let Some(edit) = edit else { return false };
+    match edit {
+        Edit::Value(i, value) => {
+            let entry = &mut properties.entries[i];
+            if entry.separator.is_empty() {
+                entry.separator = "=".to_string();
+            }
+            entry.value = Some(value);
+        }
+        Edit::Rename(i, key) => properties.entries[i].key = key.to_string(),
+        Edit::Remove(i) => {
+            properties.entries.remove(i);
+        }
+        Edit::Add(def) => properties.set(def.key, def.ty.default_value().as_deref()),
+    }
+    *string_path(model) = properties.to_string();
+    let mut first_time = true;
+    let func = Box::new(move |model: &mut Model| {
+        if first_time {
+            first_time = false;
+        } else {
+            let val = string_path(model);
+            info!("Modifying: {}", id);
+            swap(&mut old_val, val);
+        }
+    });
+    let _ = undo_history.apply(model, UndoAction { function: func });
+    true
+}
+
 impl UiState {
+    fn mirror_axis_widget(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Axis:");
+            for axis in [Axis::X, Axis::Y, Axis::Z] {
+                ui.selectable_value(&mut self.mirror_axis, axis, format!("{:?}", axis));
+            }
+        });
+    }
+
     fn set_widget_color(ui: &mut Ui, color: Color32) {
         ui.visuals_mut().widgets.hovered.fg_stroke.color = color;
         ui.visuals_mut().widgets.inactive.fg_stroke.color = color;
@@ -583,6 +730,8 @@ impl UiState {
                     bbox_max_string: format!("{}", model.header.bbox.max),
                     radius_string: format!("{}", model.header.max_radius),
                     mass_string: format!("{}", model.header.mass),
+                    com_string: format!("{}", model.header.center_of_mass),
+                    mass_properties: model.mass_properties(),
                     moir_string: format!(
                         "{:e}, {:e}, {:e}",
                         model.header.moment_of_inertia.rvec.x, model.header.moment_of_inertia.rvec.y, model.header.moment_of_inertia.rvec.z
@@ -730,6 +879,7 @@ impl UiState {
                         normal_string: format!("{}", model.turrets[turret].normal.0),
                         base_idx: model.turrets[turret].base_obj.0 as usize,
                         position_string: format!("{}", model.turrets[turret].fire_points[point]),
+                        coverage: None,
                     }
                 }
                 TurretTreeValue::Turret(turret) => {
@@ -737,6 +887,7 @@ impl UiState {
                         normal_string: format!("{}", model.turrets[turret].normal.0),
                         base_idx: model.turrets[turret].base_obj.0 as usize,
                         position_string: Default::default(),
+                        coverage: None,
                     }
                 }
                 _ => self.properties_panel = PropertiesPanel::default_turret(),
@@ -813,6 +964,8 @@ pub enum PropertiesPanel {
         bbox_max_string: String,
         radius_string: String,
         mass_string: String,
+        com_string: String,
+        mass_properties: Option<MassProperties>,
         moir_string: String,
         moiu_string: String,
         moif_string: String,
@@ -871,6 +1024,7 @@ pub enum PropertiesPanel {
         base_idx: usize,
         normal_string: String,
         position_string: String,
+        coverage: Option<TurretCoverage>,
     },
     Path {
         name_string: String,
@@ -900,6 +1054,8 @@ impl Default for PropertiesPanel {
             bbox_max_string: Default::default(),
             radius_string: Default::default(),
             mass_string: Default::default(),
+            com_string: Default::default(),
+            mass_properties: None,
             moir_string: Default::default(),
             moiu_string: Default::default(),
             moif_string: Default::default(),
@@ -986,6 +1142,7 @@ impl PropertiesPanel {
             base_idx: Default::default(),
             normal_string: Default::default(),
             position_string: Default::default(),
+            coverage: None,
         }
     }
     fn default_path() -> Self {
@@ -1019,6 +1176,10 @@ impl PofToolsGui {
         let mut buffer_ids_to_rebuild = vec![];
         let mut rebuild_all_buffers = false;
         let mut merge_duplicate_textures = false;
+        let mut split_oversized_subobject = None;
+        let mut mirror_item = None;
+        let mut properties_edited = false;
+        let mut triangulate_bad_polygons = None;
 
         macro_rules! select_new_tree_val {
             ($x:expr) => {
@@ -1044,6 +1205,8 @@ impl PofToolsGui {
                 bbox_min_string,
                 bbox_max_string,
                 mass_string,
+                com_string,
+                mass_properties,
                 radius_string,
                 moir_string,
                 moiu_string,
@@ -1169,22 +1332,81 @@ impl PofToolsGui {
                     Some(path_func(|model| &mut model.header.mass)),
                     mass_string
                 );
+                // FSO's mass estimate goes by the bounding box, so show its volume alongside the mesh's
+                let bbox_volume = self.model.header.bbox.volume();
+                let estimate = self.model.recalc_mass();
+                match mass_properties {
+                    Some(MassProperties { volume: Some(volume), .. }) => {
+                        ui.label(format!("Volume: {:.1} (bounding box: {:.1})", volume, bbox_volume));
+                        // same density convention as Model::recalc_mass, with the true volume instead
+                        let volume_estimate = 4.65 * volume.max(0.0).powf(2.0 / 3.0);
+                        ui.label(format!("FSO mass estimate: {:.1} (from volume: {:.1})", estimate, volume_estimate));
+                    }
+                    Some(props) => {
+                        ui.label(format!("Open mesh, area: {:.1} (bounding box volume: {:.1})", props.surface_area, bbox_volume));
+                        ui.label(format!("FSO mass estimate: {:.1}", estimate));
+                    }
+                    None => {
+                        ui.label(format!("FSO mass estimate: {:.1}", estimate));
+                    }
+                }
 
                 ui.horizontal(|ui| {
-                    ui.add(egui::Label::new("Moment of Inertia:"));
-                    if ui.button("Recalculate").clicked() {
-                        if let Some(mut moi) = self.model.recalc_moi() {
+                    ui.add(egui::Label::new("Center of Mass:"));
+                    if ui
+                        .add_enabled(mass_properties.is_some(), egui::Button::new("Recalculate"))
+                        .on_hover_text("Volumetric centre of detail0 and its children")
+                        .clicked()
+                    {
+                        if let Some(props) = mass_properties {
+                            let mut center_of_mass = props.center_of_mass;
                             model_action(
                                 undo_history,
                                 &mut self.model,
                                 undo_func(move |model| {
-                                    swap(&mut model.header.moment_of_inertia, &mut moi);
+                                    swap(&mut model.header.center_of_mass, &mut center_of_mass);
                                 }),
                             );
                             self.ui_state.properties_panel_dirty = true;
                         }
                     }
                 });
+                model_value_widget!(
+                    format!("{} center of mass", current_tree_selection),
+                    ui,
+                    false,
+                    Some(path_func(|model| &mut model.header.center_of_mass)),
+                    com_string
+                );
+
+                ui.horizontal(|ui| {
+                    ui.add(egui::Label::new("Moment of Inertia:"));
+                    let mut new_moi = None;
+                    if ui
+                        .button("Recalculate")
+                        .on_hover_text("FSO-style estimate, treating every vertex as an equal point mass")
+                        .clicked()
+                    {
+                        new_moi = self.model.recalc_moi();
+                    }
+                    if ui
+                        .add_enabled(mass_properties.is_some(), egui::Button::new("Volumetric"))
+                        .on_hover_text("From the volume of detail0 and its children, about the centre of mass, for the mass above")
+                        .clicked()
+                    {
+                        new_moi = mass_properties.and_then(|props| props.inverse_moi(self.model.header.mass));
+                    }
+                    if let Some(mut moi) = new_moi {
+                        model_action(
+                            undo_history,
+                            &mut self.model,
+                            undo_func(move |model| {
+                                swap(&mut model.header.moment_of_inertia, &mut moi);
+                            }),
+                        );
+                        self.ui_state.properties_panel_dirty = true;
+                    }
+                });
                 model_value_widget!(
                     format!("{} moment of inertia rvec", current_tree_selection),
                     ui,
@@ -1350,6 +1572,69 @@ impl PofToolsGui {
                     self.model.recheck_warnings(One(Warning::Detail0NonZeroOffset));
                 }
 
+                ui.separator();
+
+                ui.label("Symmetry:");
+                self.ui_state.mirror_axis_widget(ui);
+                ui.horizontal(|ui| {
+                    ui.label("Tolerance:");
+                    ui.add(
+                        DragValue::new(&mut self.ui_state.symmetry_tolerance)
+                            .speed(0.001)
+                            .clamp_range(0.0..=f32::MAX),
+                    );
+                    if ui
+                        .button("Check")
+                        .on_hover_text("Look for banks, special points, paths and subobjects that have no mirror image on the other side")
+                        .clicked()
+                    {
+                        self.ui_state.symmetry_report = Some(self.model.check_symmetry(self.ui_state.mirror_axis, self.ui_state.symmetry_tolerance));
+                    }
+                });
+                if let Some(report) = &self.ui_state.symmetry_report {
+                    if report.is_empty() {
+                        ui.label("Everything has a mirror image");
+                    }
+                    let mut clicked = None;
+                    for mismatch in report {
+                        let text = if mismatch.closest == mismatch.item {
+                            format!("{} is {:.3} off from being symmetric", mirror_item_name(&self.model, mismatch.item), mismatch.error)
+                        } else {
+                            format!(
+                                "{} and {} are {:.3} off from mirroring each other",
+                                mirror_item_name(&self.model, mismatch.item),
+                                mirror_item_name(&self.model, mismatch.closest),
+                                mismatch.error
+                            )
+                        };
+                        if ui
+                            .add(Label::new(RichText::new(text).color(WARNING_YELLOW)).sense(egui::Sense::click()))
+                            .clicked()
+                        {
+                            clicked = Some(mismatch.item);
+                        }
+                    }
+                    if let Some(item) = clicked {
+                        select_new_tree_val!(TreeValue::from_mirror_item(item));
+                    }
+                }
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label("Clearances:");
+                    if ui
+                        .button("Check")
+                        .on_hover_text("Look for subobjects reaching into their parent or siblings, including over a full turn for rotating ones. What turns up is listed with the warnings")
+                        .clicked()
+                    {
+                        // too slow to run on every change, so it only runs from here, and the overlaps it finds are kept up to date after
+                        let overlaps = self.model.check_interpenetration();
+                        self.model.warnings.retain(|warning| !matches!(warning, Warning::SubObjectInterpenetration { .. }));
+                        self.model.warnings.extend(overlaps.into_iter().map(Warning::from));
+                    }
+                });
+
                 ui.add_space(10.0);
 
                 let mut num_verts = 0;
@@ -1360,6 +1645,15 @@ impl PofToolsGui {
                 }
                 ui.label(RichText::new(format!("Total vertices: {}", num_verts)).weak());
                 ui.label(RichText::new(format!("Total normals: {}", num_norms)).weak());
+
+                if !self.model.unknown_chunks.is_empty() {
+                    ui.add_space(10.0);
+                    ui.label(RichText::new("Unknown chunks (kept as-is when saving):").weak())
+                        .on_hover_text("These chunks weren't recognized by Pof Tools, they are written back out unchanged after all other chunks");
+                    for chunk in &self.model.unknown_chunks {
+                        ui.label(RichText::new(format!("- {} ({} bytes)", chunk.id_str(), chunk.data.len())).weak());
+                    }
+                }
             }
             PropertiesPanel::SubObject {
                 bbox_min_string,
@@ -1807,16 +2101,28 @@ impl PofToolsGui {
                         self.ui_state.display_uvec_fvec = true;
                     }
 
-                    let widget_response = text_edit_multi(
+                    let mut changed = properties_editor(
                         ui,
-                        format!("{} properties", current_tree_selection),
-                        2,
+                        format!("{} properties editor", current_tree_selection),
+                        PropertiesKind::SubObject,
                         &mut self.model,
                         path_func(move |model| &mut model.sub_objects[id].properties),
                         undo_history,
                     );
-                    if widget_response.changed() {
+                    CollapsingHeader::new("Properties Raw").show(ui, |ui| {
+                        changed |= text_edit_multi(
+                            ui,
+                            format!("{} properties", current_tree_selection),
+                            2,
+                            &mut self.model,
+                            path_func(move |model| &mut model.sub_objects[id].properties),
+                            undo_history,
+                        )
+                        .changed();
+                    });
+                    if changed {
                         self.model.recheck_warnings(One(Warning::SubObjectPropertiesTooLong(id)));
+                        self.model.recheck_warnings(One(Warning::SubObjectPropertiesInvalid(id)));
                         self.ui_state.viewport_3d_dirty = true; // There may be changes to the uvec/fvec
                     };
                 } else {
@@ -2024,6 +2330,41 @@ impl PofToolsGui {
                     }
                     ui.label(vert_string);
                     ui.label(norm_string);
+
+                    if self.model.errors.contains(&Error::TooManyVerts(id)) || self.model.errors.contains(&Error::TooManyNorms(id)) {
+                        let limit = self.model.max_verts_norms_per_subobj();
+                        if ui
+                            .button("Split to fit")
+                            .on_hover_text(format!(
+                                "Splits this subobject into child subobjects with at most {} vertices and normals each, so it can be saved as version {}.\n\
+                                This cannot be undone.",
+                                limit, self.model.version
+                            ))
+                            .clicked()
+                        {
+                            split_oversized_subobject = Some(id);
+                        }
+                    }
+
+                    if [
+                        Warning::ZeroAreaPolygons(id),
+                        Warning::NonPlanarPolygons(id),
+                        Warning::ConcavePolygons(id),
+                    ]
+                    .iter()
+                    .any(|warning| self.model.warnings.contains(warning))
+                    {
+                        if ui
+                            .button("Triangulate bad polygons")
+                            .on_hover_text(
+                                "Splits the non-planar and concave polygons of this subobject into triangles, and removes the ones with no area.\n\
+                                This cannot be undone.",
+                            )
+                            .clicked()
+                        {
+                            triangulate_bad_polygons = Some(id);
+                        }
+                    }
                 }
             }
             PropertiesPanel::Texture { texture_name } => {
@@ -2117,6 +2458,23 @@ impl PofToolsGui {
                     }
                 });
 
+                if let Some(bank) = bank_num {
+                    ui.label("Properties:");
+                    if properties_editor(
+                        ui,
+                        format!("{} properties editor", current_tree_selection),
+                        PropertiesKind::ThrusterBank,
+                        &mut self.model,
+                        path_func(move |model| &mut model.thruster_banks[bank].properties),
+                        undo_history,
+                    ) {
+                        self.model.recheck_warnings(One(Warning::ThrusterPropertiesTooLong(bank)));
+                        self.model.recheck_warnings(One(Warning::ThrusterPropertiesInvalid(bank)));
+                        self.model.recheck_warnings(One(Warning::ThrusterPropertiesInvalidVersion(bank)));
+                        properties_edited = true;
+                    }
+                }
+
                 CollapsingHeader::new("Properties Raw").show(ui, |ui| {
                     if let Some(bank) = bank_num {
                         if self.model.warnings.contains(&Warning::ThrusterPropertiesInvalidVersion(bank)) {
@@ -2133,6 +2491,7 @@ impl PofToolsGui {
                         );
                         if widget_response.changed() {
                             self.model.recheck_warnings(One(Warning::ThrusterPropertiesTooLong(bank)));
+                            self.model.recheck_warnings(One(Warning::ThrusterPropertiesInvalid(bank)));
                             self.model.recheck_warnings(One(Warning::ThrusterPropertiesInvalidVersion(bank)));
                         }
 
@@ -2487,6 +2846,24 @@ impl PofToolsGui {
 
                 ui.separator();
 
+                if let Some(bay) = bay_num {
+                    ui.label("Properties:");
+                    if properties_editor(
+                        ui,
+                        format!("{} properties editor", current_tree_selection),
+                        PropertiesKind::DockingBay,
+                        &mut self.model,
+                        path_func(move |model| &mut model.docking_bays[bay].properties),
+                        undo_history,
+                    ) {
+                        self.model.recheck_warnings(One(Warning::DockingBayNameTooLong(bay)));
+                        self.model.recheck_warnings(One(Warning::DockingBayPropertiesTooLong(bay)));
+                        self.model.recheck_warnings(One(Warning::DockingBayPropertiesInvalid(bay)));
+                        self.model.recheck_warnings(One(Warning::InvalidDockParentSubmodel(bay)));
+                        properties_edited = true;
+                    }
+                }
+
                 CollapsingHeader::new("Properties Raw").show(ui, |ui| {
                     if let Some(bay) = bay_num {
                         let widget_response = text_edit_multi(
@@ -2503,6 +2880,7 @@ impl PofToolsGui {
                             }
                             self.model.recheck_warnings(One(Warning::DockingBayNameTooLong(bay)));
                             self.model.recheck_warnings(One(Warning::DockingBayPropertiesTooLong(bay)));
+                            self.model.recheck_warnings(One(Warning::DockingBayPropertiesInvalid(bay)));
                             self.model.recheck_warnings(One(Warning::InvalidDockParentSubmodel(bay)));
                         }
                     } else {
@@ -2716,6 +3094,22 @@ impl PofToolsGui {
 
                 ui.separator();
 
+                if let Some(bank) = bank_num {
+                    ui.label("Properties:");
+                    if properties_editor(
+                        ui,
+                        format!("{} properties editor", current_tree_selection),
+                        PropertiesKind::GlowBank,
+                        &mut self.model,
+                        path_func(move |model| &mut model.glow_banks[bank].properties),
+                        undo_history,
+                    ) {
+                        self.model.recheck_warnings(One(Warning::GlowBankPropertiesTooLong(bank)));
+                        self.model.recheck_warnings(One(Warning::GlowBankPropertiesInvalid(bank)));
+                        properties_edited = true;
+                    }
+                }
+
                 CollapsingHeader::new("Properties Raw").show(ui, |ui| {
                     if let Some(bank) = bank_num {
                         let widget_response = text_edit_multi(
@@ -2728,6 +3122,7 @@ impl PofToolsGui {
                         );
                         if widget_response.changed() {
                             self.model.recheck_warnings(One(Warning::GlowBankPropertiesTooLong(bank)));
+                            self.model.recheck_warnings(One(Warning::GlowBankPropertiesInvalid(bank)));
                         }
                     } else {
                         ui.add_enabled(false, egui::TextEdit::multiline(&mut String::new()).desired_rows(1));
@@ -2864,6 +3259,22 @@ impl PofToolsGui {
                     }
                 });
 
+                if let Some(point) = point_num {
+                    ui.label("Properties:");
+                    if properties_editor(
+                        ui,
+                        format!("{} properties editor", current_tree_selection),
+                        PropertiesKind::SpecialPoint,
+                        &mut self.model,
+                        path_func(move |model| &mut model.special_points[point].properties),
+                        undo_history,
+                    ) {
+                        self.model.recheck_warnings(One(Warning::SpecialPointPropertiesTooLong(point)));
+                        self.model.recheck_warnings(One(Warning::SpecialPointPropertiesInvalid(point)));
+                        properties_edited = true;
+                    }
+                }
+
                 CollapsingHeader::new("Properties Raw").show(ui, |ui| {
                     if let Some(point) = point_num {
                         let widget_response = text_edit_multi(
@@ -2879,6 +3290,7 @@ impl PofToolsGui {
                                 *name_string = new_name.to_string();
                             }
                             self.model.recheck_warnings(One(Warning::SpecialPointPropertiesTooLong(point)));
+                            self.model.recheck_warnings(One(Warning::SpecialPointPropertiesInvalid(point)));
                         }
                     } else {
                         ui.add_enabled(false, egui::TextEdit::multiline(&mut String::new()).desired_rows(1));
@@ -2921,7 +3333,7 @@ impl PofToolsGui {
                     select_new_tree_val!(TreeValue::SpecialPoints(SpecialPointTreeValue::point(new_idx)));
                 }
             }
-            PropertiesPanel::Turret { position_string, normal_string, base_idx } => {
+            PropertiesPanel::Turret { position_string, normal_string, base_idx, coverage } => {
                 ui.heading("Turret");
                 ui.separator();
 
@@ -3006,6 +3418,25 @@ impl PofToolsGui {
                 ui.label("Normal:");
                 model_value_widget!(format!("{} normal", current_tree_selection), ui, false, norm, normal_string);
 
+                const TURRET_COVERAGE_SAMPLES: usize = 256;
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(
+                            turret_num.is_some_and(|num| !self.model.turrets[num].fire_points.is_empty()),
+                            egui::Button::new("Check Coverage"),
+                        )
+                        .on_hover_text("Cast rays over the turret's hemisphere from each fire point to see how much of it the ship blocks")
+                        .clicked()
+                    {
+                        *coverage = self.model.turret_coverage(turret_num.unwrap(), TURRET_COVERAGE_SAMPLES);
+                        self.ui_state.viewport_3d_dirty = true;
+                    }
+                    if let Some(coverage) = coverage {
+                        ui.label(format!("{:.1}% blocked", coverage.blocked_fraction() * 100.0));
+                    }
+                });
+
                 ui.separator();
                 ui.add(Label::new(RichText::new("Turret Fire Points").text_style(TextStyle::Button)));
                 ui.separator();
@@ -3416,6 +3847,87 @@ impl PofToolsGui {
             }
         }
 
+        // any point with a position can be snapped onto the hull
+        let selection = self.ui_state.tree_view_selection;
+        if let Some(&mut position) = selection.get_position_ref(&mut self.model) {
+            let has_direction = selection.get_direction_ref(&mut self.model).is_some();
+            // turret fire points are relative to their gun
+            let offset = match selection {
+                TreeValue::Turrets(TurretTreeValue::TurretPoint(i, _)) => self.model.get_total_subobj_offset(self.model.turrets[i].gun_obj),
+                _ => Vec3d::ZERO,
+            };
+
+            ui.separator();
+            ui.label("Snap to Hull:");
+            ui.add_enabled(has_direction, egui::Checkbox::new(&mut self.ui_state.snap_along_normal, "Along normal"))
+                .on_hover_text("Move along the point's normal, instead of to the nearest surface");
+            ui.add_enabled(has_direction, egui::Checkbox::new(&mut self.ui_state.snap_set_normal, "Set normal"))
+                .on_hover_text("Point the normal away from the surface");
+            ui.horizontal(|ui| {
+                ui.label("Offset:");
+                ui.add(DragValue::new(&mut self.ui_state.snap_offset).speed(0.1));
+            });
+
+            if ui
+                .button("Snap")
+                .on_hover_text("Move the point onto the surface of detail0 and its children")
+                .clicked()
+            {
+                let along = match selection.get_direction_ref(&mut self.model) {
+                    Some(normal) if self.ui_state.snap_along_normal => Some(normal.0),
+                    _ => None,
+                };
+                let options = RaycastOptions { detail_level: Some(0), ..Default::default() };
+                if let Some(hit) = self.model.snap_to_surface(position + offset, along, &options) {
+                    info!("Snapped {} onto {}", selection, self.model.sub_objects[hit.obj_id].name);
+                    let mut new_position = hit.point + hit.normal * self.ui_state.snap_offset - offset;
+                    let mut new_normal = NormalVec3::try_from(hit.normal)
+                        .ok()
+                        .filter(|_| has_direction && self.ui_state.snap_set_normal);
+                    model_action(
+                        undo_history,
+                        &mut self.model,
+                        undo_func(move |model| {
+                            swap(selection.get_position_ref(model).unwrap(), &mut new_position);
+                            if let Some(normal) = &mut new_normal {
+                                swap(selection.get_direction_ref(model).unwrap(), normal);
+                            }
+                        }),
+                    );
+                    self.ui_state.properties_panel_dirty = true;
+                    self.ui_state.viewport_3d_dirty = true;
+                } else {
+                    info!("Found no surface to snap {} onto", selection);
+                }
+            }
+        }
+
+        // banks, special points, paths and subobjects can be mirrored to the other side
+        if let Some(item) = selection.to_mirror_item() {
+            ui.separator();
+            ui.label("Mirror:");
+            self.ui_state.mirror_axis_widget(ui);
+            ui.horizontal(|ui| {
+                ui.label("Sides:");
+                ui.add(TextEdit::singleline(&mut self.ui_state.mirror_naming.left).desired_width(60.0));
+                ui.add(TextEdit::singleline(&mut self.ui_state.mirror_naming.right).desired_width(60.0));
+            })
+            .response
+            .on_hover_text("The copy's name and the names in its properties have one side swapped for the other");
+
+            if ui
+                .button("Mirror")
+                .on_hover_text(if let MirrorItem::SubObject(_) = item {
+                    "Copy this subobject and its children, and any turrets on them, to the other side of the model.\nThis cannot be undone."
+                } else {
+                    "Copy this to the other side of the model."
+                })
+                .clicked()
+            {
+                mirror_item = Some(item);
+            }
+        }
+
         if merge_duplicate_textures {
             use pof::TextureId;
             let mut tex_name_map = HashMap::new();
@@ -3448,10 +3960,98 @@ impl PofToolsGui {
             self.ui_state.properties_panel_dirty = true;
         }
 
+        if let Some(id) = split_oversized_subobject {
+            // this rewrites the subobject and adds new ones after it, which the undo history can't follow
+            undo_history.clear();
+            let new_ids = self.model.split_oversized_subobject(id);
+            info!("Split {} into {} pieces", self.model.sub_objects[id].name, new_ids.len() + 1);
+            self.add_subobj_buffers(display, &new_ids);
+            buffer_ids_to_rebuild.push(id);
+            self.ui_state.properties_panel_dirty = true;
+        }
+
+        if let Some(item) = mirror_item {
+            let (axis, naming) = (self.ui_state.mirror_axis, self.ui_state.mirror_naming.clone());
+            let num_subobjs = self.model.sub_objects.len();
+            if let MirrorItem::SubObject(_) = item {
+                // copying a whole hierarchy along with its turrets is way too complicated to undo...
+                undo_history.clear();
+            }
+            let new_item = self.model.mirror(item, axis, &naming);
+            if new_item.is_some() && !matches!(item, MirrorItem::SubObject(_)) {
+                // everything else is just pushed onto the end of its list, so popping it back off undoes it
+                let mut first_time = true;
+                let mut mirrored = true;
+                model_action(
+                    undo_history,
+                    &mut self.model,
+                    undo_func(move |model| {
+                        if first_time {
+                            first_time = false;
+                            return;
+                        }
+                        if mirrored {
+                            match item {
+                                MirrorItem::PrimaryBank(_) => {
+                                    model.primary_weps.pop();
+                                }
+                                MirrorItem::SecondaryBank(_) => {
+                                    model.secondary_weps.pop();
+                                }
+                                MirrorItem::GlowBank(_) => {
+                                    model.glow_banks.pop();
+                                }
+                                MirrorItem::ThrusterBank(_) => {
+                                    model.thruster_banks.pop();
+                                }
+                                MirrorItem::SpecialPoint(_) => {
+                                    model.special_points.pop();
+                                }
+                                MirrorItem::Path(_) => {
+                                    model.paths.pop();
+                                }
+                                MirrorItem::SubObject(_) => unreachable!(),
+                            }
+                        } else {
+                            model.mirror(item, axis, &naming);
+                        }
+                        mirrored = !mirrored;
+                    }),
+                );
+            }
+            if let Some(new_item) = new_item {
+                info!("Mirrored {} across {:?} as {}", TreeValue::from_mirror_item(item), axis, TreeValue::from_mirror_item(new_item));
+                let new_ids = (num_subobjs..self.model.sub_objects.len())
+                    .map(|i| ObjectId(i as u32))
+                    .collect::<Vec<_>>();
+                self.add_subobj_buffers(display, &new_ids);
+                self.ui_state.select_new_tree_val(TreeValue::from_mirror_item(new_item));
+                self.ui_state.properties_panel_dirty = true;
+                self.ui_state.viewport_3d_dirty = true;
+            }
+        }
+
+        if let Some(id) = triangulate_bad_polygons {
+            // the collision tree gets replaced, and undoing back past that would swap stale subobject data back in
+            undo_history.clear();
+            let num_fixed = self.model.sub_objects[id].triangulate_bad_polygons();
+            info!("Triangulated or removed {} polygons of {}", num_fixed, self.model.sub_objects[id].name);
+            self.model.recheck_warnings(One(Warning::ZeroAreaPolygons(id)));
+            self.model.recheck_warnings(One(Warning::NonPlanarPolygons(id)));
+            self.model.recheck_warnings(One(Warning::ConcavePolygons(id)));
+            buffer_ids_to_rebuild.push(id);
+            self.ui_state.properties_panel_dirty = true;
+        }
+
         if reload_textures {
             self.load_textures();
         }
 
+        if properties_edited {
+            // the panel keeps its own copies of some of the fields, like names
+            self.ui_state.properties_panel_dirty = true;
+        }
+
         if self.ui_state.properties_panel_dirty {
             self.sanitize_ui_state();
             self.ui_state.refresh_properties_panel(&self.model);
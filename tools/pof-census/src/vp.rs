@@ -106,8 +106,9 @@ pub struct FileEntry<'a, 'b> {
 }
 
 impl<'a, 'b> FileEntry<'a, 'b> {
-    pub fn reader(&mut self) -> io::Result<FileSlice<'_>> {
-        FileSlice::new(self.file, self.offset.into(), self.size.into())
+    pub fn reader(&mut self) -> io::Result<io::Take<&mut File>> {
+        self.file.seek(SeekFrom::Start(self.offset.into()))?;
+        Ok(Read::take(&mut *self.file, self.size.into()))
     }
 
     pub fn as_slice(&mut self) -> io::Result<Box<[u8]>> {
@@ -121,38 +122,3 @@ impl<'a, 'b> FileEntry<'a, 'b> {
         SystemTime::UNIX_EPOCH + Duration::from_secs(self.timestamp.into())
     }
 }
-
-pub struct FileSlice<'a> {
-    file: &'a mut File,
-    start_offset: u64,
-    len: u64,
-    remaining: u64,
-}
-
-impl<'a> FileSlice<'a> {
-    fn new(file: &'a mut File, start: u64, len: u64) -> io::Result<Self> {
-        file.seek(SeekFrom::Start(start))?;
-        Ok(Self { file, start_offset: start, len, remaining: len })
-    }
-}
-
-impl Read for FileSlice<'_> {
-    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        let len = buf.len().min(self.remaining as usize);
-        let count = self.file.read(&mut buf[..len])?;
-        self.remaining -= count as u64;
-        Ok(count)
-    }
-}
-
-impl Seek for FileSlice<'_> {
-    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
-        let out = self.file.seek(match pos {
-            SeekFrom::Start(n) => SeekFrom::Start(n.min(self.len) + self.start_offset),
-            SeekFrom::End(n) => SeekFrom::Start(self.start_offset + ((self.len as i64) + n.max(0)) as u64),
-            SeekFrom::Current(n) => SeekFrom::Current(n.min(self.remaining as i64)),
-        })? - self.start_offset;
-        self.remaining = self.len - out;
-        Ok(out)
-    }
-}